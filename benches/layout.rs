@@ -15,6 +15,7 @@ fn layout(c: &mut Criterion) {
     for (wrap_name, wrap) in &[
         ("None", ct::Wrap::None),
         ("Glyph", ct::Wrap::Glyph),
+        ("WordOrGlyph", ct::Wrap::WordOrGlyph),
         ("Word", ct::Wrap::Word),
     ] {
         for (shape_name, shape) in &[
@@ -28,6 +28,12 @@ build --no-default-features --features std,syntect
 echo Build with only std and vi features
 build --no-default-features --features std,vi
 
+echo Build with only std and tracing features
+build --no-default-features --features std,tracing
+
+echo Build with only std and subset features
+build --no-default-features --features std,subset
+
 echo Build with all features
 build --all-features
 
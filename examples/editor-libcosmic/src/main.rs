@@ -71,7 +71,7 @@ impl fmt::Display for FontSize {
     }
 }
 
-static WRAP_MODE: &[Wrap] = &[Wrap::None, Wrap::Glyph, Wrap::Word];
+static WRAP_MODE: &[Wrap] = &[Wrap::None, Wrap::Glyph, Wrap::Word, Wrap::WordOrGlyph];
 
 fn main() -> cosmic::iced::Result {
     env_logger::init();
@@ -111,7 +111,7 @@ impl Window {
         let mut editor = self.editor.lock().unwrap();
         let mut font_system = FONT_SYSTEM.lock().unwrap();
         let mut editor = editor.borrow_with(&mut font_system);
-        match editor.load_text(&path, self.attrs) {
+        match editor.load_text(&path, self.attrs.clone()) {
             Ok(()) => {
                 log::info!("opened '{}'", path.display());
                 self.path_opt = Some(path);
@@ -146,7 +146,7 @@ impl Application for Window {
         #[cfg(feature = "vi")]
         let mut editor = cosmic_text::ViEditor::new(editor);
 
-        update_attrs(&mut editor, attrs);
+        update_attrs(&mut editor, attrs.clone());
 
         let mut window = Window {
             theme: Theme::dark(),
@@ -203,34 +203,34 @@ impl Application for Window {
                 }
             }
             Message::Bold(bold) => {
-                self.attrs = self.attrs.weight(if bold {
+                self.attrs = self.attrs.clone().weight(if bold {
                     cosmic_text::Weight::BOLD
                 } else {
                     cosmic_text::Weight::NORMAL
                 });
 
                 let mut editor = self.editor.lock().unwrap();
-                update_attrs(&mut *editor, self.attrs);
+                update_attrs(&mut *editor, self.attrs.clone());
             }
             Message::Italic(italic) => {
-                self.attrs = self.attrs.style(if italic {
+                self.attrs = self.attrs.clone().style(if italic {
                     cosmic_text::Style::Italic
                 } else {
                     cosmic_text::Style::Normal
                 });
 
                 let mut editor = self.editor.lock().unwrap();
-                update_attrs(&mut *editor, self.attrs);
+                update_attrs(&mut *editor, self.attrs.clone());
             }
             Message::Monospaced(monospaced) => {
-                self.attrs = self.attrs.family(if monospaced {
+                self.attrs = self.attrs.clone().family(if monospaced {
                     cosmic_text::Family::Monospace
                 } else {
                     cosmic_text::Family::SansSerif
                 });
 
                 let mut editor = self.editor.lock().unwrap();
-                update_attrs(&mut *editor, self.attrs);
+                update_attrs(&mut *editor, self.attrs.clone());
             }
             Message::FontSizeChanged(font_size) => {
                 self.font_size = font_size;
@@ -260,7 +260,7 @@ impl Application for Window {
 
                 let Color { r, g, b, a } = self.theme.cosmic().on_bg_color().into();
                 let as_u8 = |component: f32| (component * 255.0) as u8;
-                self.attrs = self.attrs.color(cosmic_text::Color::rgba(
+                self.attrs = self.attrs.clone().color(cosmic_text::Color::rgba(
                     as_u8(r),
                     as_u8(g),
                     as_u8(b),
@@ -276,7 +276,7 @@ impl Application for Window {
                     "Dark" | _ => editor.update_theme("base16-eighties.dark"),
                 };
 
-                update_attrs(&mut *editor, self.attrs);
+                update_attrs(&mut *editor, self.attrs.clone());
             }
         }
 
@@ -377,7 +377,7 @@ impl Application for Window {
 
 fn update_attrs<T: Edit>(editor: &mut T, attrs: Attrs) {
     editor.buffer_mut().lines.iter_mut().for_each(|line| {
-        line.set_attrs_list(AttrsList::new(attrs));
+        line.set_attrs_list(AttrsList::new(attrs.clone()));
     });
 }
 
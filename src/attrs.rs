@@ -1,15 +1,21 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use alloc::sync::Arc;
 #[cfg(not(feature = "std"))]
 use alloc::{
     string::{String, ToString},
     vec::Vec,
 };
-use core::ops::Range;
+use core::{
+    fmt,
+    ops::{BitOr, BitOrAssign, Range},
+};
 
 pub use fontdb::{Family, Stretch, Style, Weight};
 use rangemap::RangeMap;
 
+use crate::Align;
+
 /// Text color
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, Eq, Hash, PartialEq)]
 pub struct Color(pub u32);
@@ -99,8 +105,113 @@ impl FamilyOwned {
     }
 }
 
+/// A set of text decorations, combined with `|`
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TextDecoration(u8);
+
+impl TextDecoration {
+    /// No decoration
+    pub const NONE: Self = Self(0);
+    /// Line below the baseline
+    pub const UNDERLINE: Self = Self(1 << 0);
+    /// Line through the middle of the text
+    pub const STRIKETHROUGH: Self = Self(1 << 1);
+    /// Line above the text
+    pub const OVERLINE: Self = Self(1 << 2);
+
+    /// Check if there are no decorations
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Check if all decorations in `other` are set
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Set or clear the decorations in `other`
+    pub fn set(&mut self, other: Self, value: bool) {
+        if value {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+}
+
+impl BitOr for TextDecoration {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for TextDecoration {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+/// An OpenType feature tag, like `*b"smcp"` for small capitals
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Tag(pub [u8; 4]);
+
+impl Tag {
+    /// Create a tag from its four bytes
+    pub const fn new(bytes: &[u8; 4]) -> Self {
+        Self(*bytes)
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{}", byte as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// Values of variable font axes, see [`Attrs::font_variation`]
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FontVariations {
+    /// Axes and the `f32` bits of their values, in the order they were first set
+    axes: Vec<(Tag, u32)>,
+}
+
+impl FontVariations {
+    /// Get the value of an axis, or None if it is not set
+    pub fn get(&self, tag: Tag) -> Option<f32> {
+        self.axes
+            .iter()
+            .find(|(axis_tag, _)| *axis_tag == tag)
+            .map(|(_, bits)| f32::from_bits(*bits))
+    }
+
+    /// Set the value of an axis, replacing it if it was already set
+    pub fn set(&mut self, tag: Tag, value: f32) {
+        match self.axes.iter_mut().find(|(axis_tag, _)| *axis_tag == tag) {
+            Some(axis) => axis.1 = value.to_bits(),
+            None => self.axes.push((tag, value.to_bits())),
+        }
+    }
+
+    /// Iterate over the axes and their values, in the order they were first set
+    pub fn iter(&self) -> impl Iterator<Item = (Tag, f32)> + '_ {
+        self.axes
+            .iter()
+            .map(|(tag, bits)| (*tag, f32::from_bits(*bits)))
+    }
+
+    /// Get the axes and their values, in the order they were first set
+    pub fn to_vec(&self) -> Vec<(Tag, f32)> {
+        self.iter().collect()
+    }
+}
+
 /// Text attributes
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Attrs<'a> {
     //TODO: should this be an option?
     pub color_opt: Option<Color>,
@@ -109,6 +220,20 @@ pub struct Attrs<'a> {
     pub style: Style,
     pub weight: Weight,
     pub metadata: usize,
+    pub text_decoration: TextDecoration,
+    pub decoration_color_opt: Option<Color>,
+    pub background_opt: Option<Color>,
+    pub kerning: bool,
+    pub ligatures: bool,
+    pub align_opt: Option<Align>,
+    /// `f32` bits of extra advance after each glyph, in pixels, stored as bits to allow hashing
+    letter_spacing_bits: u32,
+    /// `f32` bits of extra advance after each whitespace glyph, in pixels
+    word_spacing_bits: u32,
+    /// OpenType features and their values, applied after `kerning` and `ligatures`
+    pub font_features: Option<Arc<Vec<(Tag, u32)>>>,
+    /// Variable font axes and their values
+    pub font_variations: Option<Arc<FontVariations>>,
 }
 
 impl<'a> Attrs<'a> {
@@ -123,6 +248,16 @@ impl<'a> Attrs<'a> {
             style: Style::Normal,
             weight: Weight::NORMAL,
             metadata: 0,
+            text_decoration: TextDecoration::NONE,
+            decoration_color_opt: None,
+            background_opt: None,
+            kerning: true,
+            ligatures: true,
+            align_opt: None,
+            letter_spacing_bits: 0.0f32.to_bits(),
+            word_spacing_bits: 0.0f32.to_bits(),
+            font_features: None,
+            font_variations: None,
         }
     }
 
@@ -162,6 +297,104 @@ impl<'a> Attrs<'a> {
         self
     }
 
+    /// Set [TextDecoration]
+    pub fn text_decoration(mut self, text_decoration: TextDecoration) -> Self {
+        self.text_decoration = text_decoration;
+        self
+    }
+
+    /// Set or clear [`TextDecoration::OVERLINE`]
+    pub fn overline(mut self, overline: bool) -> Self {
+        self.text_decoration.set(TextDecoration::OVERLINE, overline);
+        self
+    }
+
+    /// Set [Color] of decorations like underline, falling back to the text color if not set
+    pub fn decoration_color(mut self, color: Color) -> Self {
+        self.decoration_color_opt = Some(color);
+        self
+    }
+
+    /// Set background [Color]
+    pub fn background(mut self, color: Color) -> Self {
+        self.background_opt = Some(color);
+        self
+    }
+
+    /// Set kerning, disabling the `kern` OpenType feature if false
+    pub fn kerning(mut self, kerning: bool) -> Self {
+        self.kerning = kerning;
+        self
+    }
+
+    /// Set ligatures, disabling the `liga` and `clig` OpenType features if false
+    pub fn ligatures(mut self, ligatures: bool) -> Self {
+        self.ligatures = ligatures;
+        self
+    }
+
+    /// Set [Align] of the paragraph, used when these are the attributes at the start of a line
+    pub fn align(mut self, align: Align) -> Self {
+        self.align_opt = Some(align);
+        self
+    }
+
+    /// Set letter spacing, the extra advance after each glyph in pixels, which may be negative
+    pub fn letter_spacing(mut self, letter_spacing: f32) -> Self {
+        self.letter_spacing_bits = letter_spacing.to_bits();
+        self
+    }
+
+    /// Set word spacing, the extra advance after each whitespace glyph in pixels, which may be
+    /// negative
+    pub fn word_spacing(mut self, word_spacing: f32) -> Self {
+        self.word_spacing_bits = word_spacing.to_bits();
+        self
+    }
+
+    /// Get letter spacing, see [`Attrs::letter_spacing`]
+    pub fn get_letter_spacing(&self) -> f32 {
+        f32::from_bits(self.letter_spacing_bits)
+    }
+
+    /// Get word spacing, see [`Attrs::word_spacing`]
+    pub fn get_word_spacing(&self) -> f32 {
+        f32::from_bits(self.word_spacing_bits)
+    }
+
+    /// Set an OpenType feature, like `attrs.font_feature(*b"smcp", 1)` to enable small capitals
+    /// or `attrs.font_feature(*b"calt", 0)` to disable contextual alternates
+    ///
+    /// This replaces the value of the feature if it was already set.
+    pub fn font_feature(mut self, tag: [u8; 4], value: u32) -> Self {
+        let tag = Tag(tag);
+        let font_features = Arc::make_mut(self.font_features.get_or_insert_with(Default::default));
+        match font_features
+            .iter_mut()
+            .find(|(feature_tag, _)| *feature_tag == tag)
+        {
+            Some(feature) => feature.1 = value,
+            None => font_features.push((tag, value)),
+        }
+        self
+    }
+
+    /// Set a variable font axis, like `attrs.font_variation(*b"wght", 650.0)` for the weight axis
+    ///
+    /// This replaces the value of the axis if it was already set. Axes that the font does not
+    /// have are ignored. The axes are used for shaping and are passed on to
+    /// [`crate::CacheKey::font_variations`] for rasterizing.
+    pub fn font_variation(mut self, tag: [u8; 4], value: f32) -> Self {
+        Arc::make_mut(self.font_variations.get_or_insert_with(Default::default))
+            .set(Tag(tag), value);
+        self
+    }
+
+    /// Create an owned copy of these attributes, the same as [`AttrsOwned::new`]
+    pub fn clone_owned(&self) -> AttrsOwned {
+        AttrsOwned::new(self.clone())
+    }
+
     /// Check if font matches
     pub fn matches(&self, face: &fontdb::FaceInfo) -> bool {
         //TODO: smarter way of including emoji
@@ -171,15 +404,85 @@ impl<'a> Attrs<'a> {
                 && face.stretch == self.stretch)
     }
 
+    /// Get the attributes that affect shaping, see [`ShapingKey`]
+    pub fn compatible_for_shaping(&self) -> ShapingKey<'a> {
+        ShapingKey {
+            family: self.family,
+            stretch: self.stretch,
+            style: self.style,
+            weight: self.weight,
+            kerning: self.kerning,
+            ligatures: self.ligatures,
+            font_features: self.font_features.clone(),
+            font_variations: self.font_variations.clone(),
+        }
+    }
+
     /// Check if this set of attributes can be shaped with another
     pub fn compatible(&self, other: &Self) -> bool {
-        self.family == other.family
-            && self.stretch == other.stretch
-            && self.style == other.style
-            && self.weight == other.weight
+        self.compatible_for_shaping() == other.compatible_for_shaping()
     }
 }
 
+impl<'a> fmt::Display for Attrs<'a> {
+    /// Human-readable font description, like `Fira Sans Bold Italic`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.family {
+            Family::Name(name) => write!(f, "{}", name)?,
+            Family::Serif => write!(f, "Serif")?,
+            Family::SansSerif => write!(f, "Sans Serif")?,
+            Family::Cursive => write!(f, "Cursive")?,
+            Family::Fantasy => write!(f, "Fantasy")?,
+            Family::Monospace => write!(f, "Monospace")?,
+        }
+
+        let weight = match self.weight.0 {
+            0..=149 => "Thin",
+            150..=249 => "Extra Light",
+            250..=349 => "Light",
+            350..=449 => "Regular",
+            450..=549 => "Medium",
+            550..=649 => "Semi Bold",
+            650..=749 => "Bold",
+            750..=849 => "Extra Bold",
+            _ => "Black",
+        };
+        let style = match self.style {
+            Style::Normal => None,
+            Style::Italic => Some("Italic"),
+            Style::Oblique => Some("Oblique"),
+        };
+
+        match (weight, style) {
+            ("Regular", Some(style)) => write!(f, " {}", style),
+            (weight, Some(style)) => write!(f, " {} {}", weight, style),
+            (weight, None) => write!(f, " {}", weight),
+        }
+    }
+}
+
+impl fmt::Display for AttrsOwned {
+    /// Human-readable font description, like `Fira Sans Bold Italic`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_attrs().fmt(f)
+    }
+}
+
+/// The subset of [`Attrs`] that affects shaping
+///
+/// Two spans of text can be shaped together if and only if their keys are equal.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ShapingKey<'a> {
+    pub family: Family<'a>,
+    pub stretch: Stretch,
+    pub style: Style,
+    pub weight: Weight,
+    pub kerning: bool,
+    pub ligatures: bool,
+    pub font_features: Option<Arc<Vec<(Tag, u32)>>>,
+    pub font_variations: Option<Arc<FontVariations>>,
+}
+
 /// An owned version of [`Attrs`]
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct AttrsOwned {
@@ -190,9 +493,24 @@ pub struct AttrsOwned {
     pub style: Style,
     pub weight: Weight,
     pub metadata: usize,
+    pub text_decoration: TextDecoration,
+    pub decoration_color_opt: Option<Color>,
+    pub background_opt: Option<Color>,
+    pub kerning: bool,
+    pub ligatures: bool,
+    pub align_opt: Option<Align>,
+    /// `f32` bits of extra advance after each glyph, in pixels, stored as bits to allow hashing
+    letter_spacing_bits: u32,
+    /// `f32` bits of extra advance after each whitespace glyph, in pixels
+    word_spacing_bits: u32,
+    /// OpenType features and their values, applied after `kerning` and `ligatures`
+    pub font_features: Option<Arc<Vec<(Tag, u32)>>>,
+    /// Variable font axes and their values
+    pub font_variations: Option<Arc<FontVariations>>,
 }
 
 impl AttrsOwned {
+    /// Create an owned copy of [`Attrs`], the same as [`Attrs::clone_owned`]
     pub fn new(attrs: Attrs) -> Self {
         Self {
             color_opt: attrs.color_opt,
@@ -201,9 +519,20 @@ impl AttrsOwned {
             style: attrs.style,
             weight: attrs.weight,
             metadata: attrs.metadata,
+            text_decoration: attrs.text_decoration,
+            decoration_color_opt: attrs.decoration_color_opt,
+            background_opt: attrs.background_opt,
+            kerning: attrs.kerning,
+            ligatures: attrs.ligatures,
+            align_opt: attrs.align_opt,
+            letter_spacing_bits: attrs.letter_spacing_bits,
+            word_spacing_bits: attrs.word_spacing_bits,
+            font_features: attrs.font_features,
+            font_variations: attrs.font_variations,
         }
     }
 
+    /// Borrow as [`Attrs`], referencing the owned family name
     pub fn as_attrs(&self) -> Attrs {
         Attrs {
             color_opt: self.color_opt,
@@ -212,8 +541,40 @@ impl AttrsOwned {
             style: self.style,
             weight: self.weight,
             metadata: self.metadata,
+            text_decoration: self.text_decoration,
+            decoration_color_opt: self.decoration_color_opt,
+            background_opt: self.background_opt,
+            kerning: self.kerning,
+            ligatures: self.ligatures,
+            align_opt: self.align_opt,
+            letter_spacing_bits: self.letter_spacing_bits,
+            word_spacing_bits: self.word_spacing_bits,
+            font_features: self.font_features.clone(),
+            font_variations: self.font_variations.clone(),
         }
     }
+
+    /// Get letter spacing, see [`Attrs::letter_spacing`]
+    pub fn get_letter_spacing(&self) -> f32 {
+        f32::from_bits(self.letter_spacing_bits)
+    }
+
+    /// Get word spacing, see [`Attrs::word_spacing`]
+    pub fn get_word_spacing(&self) -> f32 {
+        f32::from_bits(self.word_spacing_bits)
+    }
+}
+
+impl<'a> From<Attrs<'a>> for AttrsOwned {
+    fn from(attrs: Attrs<'a>) -> Self {
+        Self::new(attrs)
+    }
+}
+
+impl<'a> From<&'a AttrsOwned> for Attrs<'a> {
+    fn from(attrs: &'a AttrsOwned) -> Self {
+        attrs.as_attrs()
+    }
 }
 
 /// List of text attributes to apply to a line
@@ -258,6 +619,17 @@ impl AttrsList {
         self.spans.insert(range, AttrsOwned::new(attrs));
     }
 
+    /// Merge adjacent spans with equal attributes, and remove spans equal to the defaults
+    pub fn compact(&mut self) {
+        let spans = core::mem::take(&mut self.spans);
+        for (range, attrs) in spans {
+            if attrs != self.defaults {
+                // RangeMap merges adjacent ranges with equal values on insert
+                self.spans.insert(range, attrs);
+            }
+        }
+    }
+
     /// Get the attribute span for an index
     ///
     /// This returns a span that contains the index
@@ -302,4 +674,13 @@ impl AttrsList {
         }
         new
     }
+
+    /// Split attributes list at an offset, returning the halves before and after it
+    ///
+    /// Unlike [`AttrsList::split_off`], this leaves `self` unchanged.
+    pub fn split_at(&self, index: usize) -> (Self, Self) {
+        let mut start = self.clone();
+        let end = start.split_off(index);
+        (start, end)
+    }
 }
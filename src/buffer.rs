@@ -1,16 +1,23 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
 #[cfg(not(feature = "std"))]
 use alloc::{
     string::{String, ToString},
     vec::Vec,
 };
-use core::{cmp, fmt};
+use core::{cmp, fmt, iter::Enumerate, mem, ops::Range, ptr, slice};
+use unicode_normalization::UnicodeNormalization;
 use unicode_segmentation::UnicodeSegmentation;
 
+use crate::buffer_line::{LayoutOptions, ShapeKey, DEFAULT_TAB_SIZE};
+#[cfg(feature = "swash")]
+use crate::TextDecoration;
 use crate::{
-    Attrs, AttrsList, BidiParagraphs, BorrowedWithFontSystem, BufferLine, Color, FontSystem,
-    LayoutGlyph, LayoutLine, ShapeBuffer, ShapeLine, Shaping, Wrap,
+    Align, Attrs, AttrsList, AttrsOwned, BidiInfo, BidiParagraphs, BorrowedWithFontSystem,
+    BufferLine, Color, Font, FontSystem, LayoutGlyph, LayoutLine, Overflow, Rect, ShapeBuffer,
+    ShapeLine, Shaping, Wrap,
 };
 
 /// Current cursor location
@@ -53,6 +60,16 @@ impl Cursor {
     }
 }
 
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, index {} ({:?})",
+            self.line, self.index, self.affinity
+        )
+    }
+}
+
 /// Whether to associate cursors placed at a boundary between runs with the run before or after it.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub enum Affinity {
@@ -125,8 +142,16 @@ pub struct LayoutRun<'a> {
     pub line_y: f32,
     /// Y offset to top of line
     pub line_top: f32,
+    /// Height of line
+    pub line_height: f32,
     /// Width of line
     pub line_w: f32,
+    /// Maximum ascent of the glyphs in line
+    pub glyph_ascent: f32,
+    /// Maximum descent of the glyphs in line
+    pub glyph_descent: f32,
+    /// True if this is not the first layout line of the original text line, because of wrapping
+    pub is_continuation: bool,
 }
 
 impl<'a> LayoutRun<'a> {
@@ -168,6 +193,48 @@ impl<'a> LayoutRun<'a> {
         }
     }
 
+    /// Return the start and end of the text of this run between the glyph boundaries nearest to
+    /// `x_start` and `x_end`, as selected by a column selection, or None if the run is empty
+    pub fn column_range(&self, x_start: f32, x_end: f32) -> Option<(Cursor, Cursor)> {
+        let (range, _) = column_bounds(self.glyphs, x_start, x_end)?;
+        Some((
+            Cursor::new_with_affinity(self.line_i, range.start, Affinity::After),
+            Cursor::new_with_affinity(self.line_i, range.end, Affinity::Before),
+        ))
+    }
+
+    /// Return the pixel span `Some((x_left, x_width))` of a column selection between `x_start` and
+    /// `x_end` within this run, snapped to the nearest glyph boundaries, or None if the run is
+    /// empty. See [`LayoutRun::column_range`] for the selected text.
+    pub fn highlight_columns(&self, x_start: f32, x_end: f32) -> Option<(f32, f32)> {
+        let (_, span) = column_bounds(self.glyphs, x_start, x_end)?;
+        Some(span)
+    }
+
+    /// Get the bounding rectangle of a glyph of this run, relative to the buffer
+    ///
+    /// The rectangle covers the advance of the glyph and the ascent and descent of the line.
+    pub fn glyph_bounds(&self, glyph: &LayoutGlyph) -> Rect {
+        Rect::new(
+            glyph.x,
+            self.line_y - self.glyph_ascent,
+            glyph.w,
+            self.glyph_ascent + self.glyph_descent,
+        )
+    }
+
+    /// Get the sorted, unique byte indices of the glyph cluster boundaries in this run
+    pub fn cluster_boundaries(&self) -> Vec<usize> {
+        let mut boundaries: Vec<usize> = self
+            .glyphs
+            .iter()
+            .flat_map(|glyph| [glyph.start, glyph.end])
+            .collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        boundaries
+    }
+
     fn cursor_from_glyph_left(&self, glyph: &LayoutGlyph) -> Cursor {
         if self.rtl {
             Cursor::new_with_affinity(self.line_i, glyph.end, Affinity::Before)
@@ -185,6 +252,46 @@ impl<'a> LayoutRun<'a> {
     }
 }
 
+/// Find the glyph boundary nearest to `x`, returning its byte index and x position
+fn nearest_glyph_boundary(glyphs: &[LayoutGlyph], x: f32) -> Option<(usize, f32)> {
+    let mut nearest_opt: Option<(usize, f32)> = None;
+    for glyph in glyphs.iter() {
+        let (left, right) = if glyph.level.is_rtl() {
+            (glyph.end, glyph.start)
+        } else {
+            (glyph.start, glyph.end)
+        };
+        for (index, edge_x) in [(left, glyph.x), (right, glyph.x + glyph.w)] {
+            let closer = match nearest_opt {
+                Some((_, nearest_x)) => (edge_x - x).abs() < (nearest_x - x).abs(),
+                None => true,
+            };
+            if closer {
+                nearest_opt = Some((index, edge_x));
+            }
+        }
+    }
+    nearest_opt
+}
+
+/// Get the byte range and the pixel span `(x_left, x_width)` of a column selection between
+/// `x_start` and `x_end` in a line of glyphs, or None if there are no glyphs
+pub(crate) fn column_bounds(
+    glyphs: &[LayoutGlyph],
+    x_start: f32,
+    x_end: f32,
+) -> Option<(Range<usize>, (f32, f32))> {
+    let (start, start_x) = nearest_glyph_boundary(glyphs, x_start)?;
+    let (end, end_x) = nearest_glyph_boundary(glyphs, x_end)?;
+    let range = cmp::min(start, end)..cmp::max(start, end);
+    let (x_left, x_right) = if start_x < end_x {
+        (start_x, end_x)
+    } else {
+        (end_x, start_x)
+    };
+    Some((range, (x_left, x_right - x_left)))
+}
+
 /// An iterator of visible text lines, see [`LayoutRun`]
 #[derive(Debug)]
 pub struct LayoutRunIter<'b> {
@@ -193,40 +300,57 @@ pub struct LayoutRunIter<'b> {
     layout_i: usize,
     remaining_len: usize,
     total_layout: i32,
+    line_top: f32,
 }
 
 impl<'b> LayoutRunIter<'b> {
     pub fn new(buffer: &'b Buffer) -> Self {
-        let total_layout_lines: usize = buffer
-            .lines
-            .iter()
-            .map(|line| {
-                line.layout_opt()
-                    .as_ref()
-                    .map(|layout| layout.len())
-                    .unwrap_or_default()
-            })
-            .sum();
-        let top_cropped_layout_lines =
-            total_layout_lines.saturating_sub(buffer.scroll.try_into().unwrap_or_default());
-        let maximum_lines = if buffer.metrics.line_height == 0.0 {
-            0
-        } else {
-            (buffer.height / buffer.metrics.line_height) as i32
-        };
-        let bottom_cropped_layout_lines =
-            if top_cropped_layout_lines > maximum_lines.try_into().unwrap_or_default() {
-                maximum_lines.try_into().unwrap_or_default()
-            } else {
-                top_cropped_layout_lines
-            };
+        // Count the layout lines after scroll that fit entirely within the buffer height
+        let mut visible_layout_lines = 0;
+        let mut total_layout = 0;
+        let mut line_top = buffer.baseline_offset;
+        'lines: for line in buffer.lines.iter() {
+            let layout_len = line
+                .layout_opt()
+                .as_ref()
+                .map(|layout| layout.len())
+                .unwrap_or_default();
+            let line_height = buffer.line_height(line);
+            let paragraph_style = line.paragraph_style();
+            for layout_i in 0..layout_len {
+                if buffer
+                    .max_lines_opt
+                    .map_or(false, |max_lines| total_layout >= max_lines as i32)
+                {
+                    break 'lines;
+                }
+                let scrolled = total_layout < buffer.scroll;
+                total_layout += 1;
+                if scrolled {
+                    continue;
+                }
+
+                if layout_i == 0 {
+                    line_top += paragraph_style.spacing_before;
+                }
+                if line_height == 0.0 || line_top + line_height > buffer.size().1 {
+                    break 'lines;
+                }
+                line_top += line_height;
+                if layout_i + 1 == layout_len {
+                    line_top += paragraph_style.spacing_after;
+                }
+                visible_layout_lines += 1;
+            }
+        }
 
         Self {
             buffer,
             line_i: 0,
             layout_i: 0,
-            remaining_len: bottom_cropped_layout_lines,
+            remaining_len: visible_layout_lines,
             total_layout: 0,
+            line_top: buffer.baseline_offset,
         }
     }
 }
@@ -240,10 +364,14 @@ impl<'b> Iterator for LayoutRunIter<'b> {
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(line) = self.buffer.lines.get(self.line_i) {
-            let shape = line.shape_opt().as_ref()?;
+            let shape = line.shape_opt()?;
             let layout = line.layout_opt().as_ref()?;
+            let line_height = self.buffer.line_height(line);
+            let paragraph_style = line.paragraph_style();
             while let Some(layout_line) = layout.get(self.layout_i) {
+                let first_layout = self.layout_i == 0;
                 self.layout_i += 1;
+                let last_layout = self.layout_i == layout.len();
 
                 let scrolled = self.total_layout < self.buffer.scroll;
                 self.total_layout += 1;
@@ -251,21 +379,24 @@ impl<'b> Iterator for LayoutRunIter<'b> {
                     continue;
                 }
 
-                let line_top = self
-                    .total_layout
-                    .saturating_sub(self.buffer.scroll)
-                    .saturating_sub(1) as f32
-                    * self.buffer.metrics.line_height;
+                if first_layout {
+                    self.line_top += paragraph_style.spacing_before;
+                }
+                let line_top = self.line_top;
                 let glyph_height = layout_line.max_ascent + layout_line.max_descent;
-                let centering_offset = (self.buffer.metrics.line_height - glyph_height) / 2.0;
+                let centering_offset = (line_height - glyph_height) / 2.0;
                 let line_y = line_top + centering_offset + layout_line.max_ascent;
 
-                if line_top + centering_offset > self.buffer.height {
+                if line_top + centering_offset > self.buffer.size().1 {
                     return None;
                 }
 
                 return self.remaining_len.checked_sub(1).map(|num| {
                     self.remaining_len = num;
+                    self.line_top += line_height;
+                    if last_layout {
+                        self.line_top += paragraph_style.spacing_after;
+                    }
                     LayoutRun {
                         line_i: self.line_i,
                         text: line.text(),
@@ -273,7 +404,11 @@ impl<'b> Iterator for LayoutRunIter<'b> {
                         glyphs: &layout_line.glyphs,
                         line_y,
                         line_top,
+                        line_height,
                         line_w: layout_line.w,
+                        glyph_ascent: layout_line.max_ascent,
+                        glyph_descent: layout_line.max_descent,
+                        is_continuation: !first_layout,
                     }
                 });
             }
@@ -318,6 +453,158 @@ impl fmt::Display for Metrics {
     }
 }
 
+/// Line endings used to split text into lines, see [`Buffer::set_text_with_newline_behavior`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NewlineBehavior {
+    /// Split on `\n`
+    Lf,
+    /// Split on `\r\n`
+    CrLf,
+    /// Split on `\r`
+    Cr,
+    /// Split on the most common line ending of the text, see [`NewlineBehavior::detect`]
+    Auto,
+}
+
+impl NewlineBehavior {
+    /// Detect the most common line ending of the text, preferring [`NewlineBehavior::Lf`] if
+    /// there is a tie or no line endings
+    pub fn detect(text: &str) -> Self {
+        let bytes = text.as_bytes();
+        let mut lf = 0;
+        let mut crlf = 0;
+        let mut cr = 0;
+        for (i, byte) in bytes.iter().enumerate() {
+            match byte {
+                b'\r' if bytes.get(i + 1) == Some(&b'\n') => crlf += 1,
+                b'\r' => cr += 1,
+                b'\n' if i > 0 && bytes[i - 1] == b'\r' => {}
+                b'\n' => lf += 1,
+                _ => {}
+            }
+        }
+
+        if crlf > lf && crlf >= cr {
+            Self::CrLf
+        } else if cr > lf && cr > crlf {
+            Self::Cr
+        } else {
+            Self::Lf
+        }
+    }
+
+    fn separator(self, text: &str) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::Cr => "\r",
+            Self::Auto => Self::detect(text).separator(text),
+        }
+    }
+}
+
+/// Shaped lines shared between [`BufferLine`]s with the same text and attributes
+///
+/// Shapes are stored by the hash of their [`ShapeKey`], and the key is compared on lookup so a
+/// hash collision is a miss. When full, the least recently used shape is evicted. All shapes are
+/// dropped when the fonts of the [`FontSystem`] change.
+#[derive(Debug)]
+struct ShapeCache {
+    capacity: usize,
+    db_generation: u64,
+    /// Key, shape, and last use of each cached shape, by the hash of its key
+    shapes: BTreeMap<u64, (ShapeKey, Arc<ShapeLine>, u64)>,
+    /// Hash of each cached shape, by its last use
+    uses: BTreeMap<u64, u64>,
+    use_count: u64,
+}
+
+impl ShapeCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            db_generation: 0,
+            shapes: BTreeMap::new(),
+            uses: BTreeMap::new(),
+            use_count: 0,
+        }
+    }
+
+    /// Shape `line` if it is not shaped, reusing a cached shape if possible
+    fn shape_line(
+        &mut self,
+        line: &mut BufferLine,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+    ) {
+        if line.shape_opt().is_some() {
+            return;
+        }
+
+        if self.db_generation != font_system.db_generation() {
+            self.db_generation = font_system.db_generation();
+            self.shapes.clear();
+            self.uses.clear();
+        }
+
+        let hash = line.shape_hash();
+        if let Some((key, shape, last_use)) = self.shapes.get_mut(&hash) {
+            if key.matches(line) {
+                line.set_shape(shape.clone());
+                self.uses.remove(&*last_use);
+                self.use_count += 1;
+                *last_use = self.use_count;
+                self.uses.insert(self.use_count, hash);
+                return;
+            }
+        }
+
+        line.shape_in_buffer(scratch, font_system);
+        if self.capacity == 0 {
+            return;
+        }
+        let shape = match line.shape_arc_opt() {
+            Some(shape) => shape.clone(),
+            None => return,
+        };
+        // Replace the shape of a colliding line
+        if let Some((_, _, last_use)) = self.shapes.remove(&hash) {
+            self.uses.remove(&last_use);
+        }
+        while self.shapes.len() >= self.capacity {
+            let (last_use, old_hash) = match self.uses.iter().next() {
+                Some((last_use, old_hash)) => (*last_use, *old_hash),
+                None => break,
+            };
+            self.uses.remove(&last_use);
+            self.shapes.remove(&old_hash);
+        }
+        self.use_count += 1;
+        self.shapes
+            .insert(hash, (line.shape_key(), shape, self.use_count));
+        self.uses.insert(self.use_count, hash);
+    }
+}
+
+/// Lines of a [`Buffer`] replaced by an edit, used for undo and redo
+#[derive(Debug)]
+struct BufferEdit {
+    /// Index of the first replaced line
+    start: usize,
+    /// Number of lines after the replaced lines, which the edit does not change
+    after: usize,
+    /// Text, attributes, and styles of the replaced lines
+    lines: Vec<BufferLine>,
+}
+
+/// [`BufferEdit`]s for undo and redo
+#[derive(Debug)]
+struct BufferHistory {
+    max_depth: usize,
+    undo: VecDeque<BufferEdit>,
+    redo: Vec<BufferEdit>,
+}
+
 /// A buffer of text that is shaped and laid out
 #[derive(Debug)]
 pub struct Buffer {
@@ -326,10 +613,28 @@ pub struct Buffer {
     metrics: Metrics,
     width: f32,
     height: f32,
+    min_height: f32,
     scroll: i32,
+    baseline_offset: f32,
+    line_spacing: f32,
+    estimated_line_height_opt: Option<f32>,
+    /// Line height of the default font at the current font size, updated when shaping
+    font_line_height_opt: Option<f32>,
+    /// Font size, default attributes of the first line, and font database generation that
+    /// `font_line_height_opt` was computed for
+    font_line_height_key_opt: Option<(f32, AttrsOwned, u64)>,
     /// True if a redraw is requires. Set to false after processing
     redraw: bool,
     wrap: Wrap,
+    tab_stops: Vec<f32>,
+    tab_size: u8,
+    wrap_balance: bool,
+    align_opt: Option<Align>,
+    overflow: Overflow,
+    ellipsis: String,
+    max_lines_opt: Option<usize>,
+    history_opt: Option<BufferHistory>,
+    shape_cache_opt: Option<ShapeCache>,
 
     /// Scratch buffer for shaping and laying out.
     scratch: ShapeBuffer,
@@ -354,9 +659,24 @@ impl Buffer {
             metrics,
             width: 0.0,
             height: 0.0,
+            min_height: 0.0,
             scroll: 0,
+            baseline_offset: 0.0,
+            line_spacing: 1.0,
+            estimated_line_height_opt: None,
+            font_line_height_opt: None,
+            font_line_height_key_opt: None,
             redraw: false,
             wrap: Wrap::Word,
+            tab_stops: Vec::new(),
+            tab_size: DEFAULT_TAB_SIZE,
+            wrap_balance: false,
+            align_opt: None,
+            overflow: Overflow::Clip,
+            ellipsis: String::from("…"),
+            max_lines_opt: None,
+            history_opt: None,
+            shape_cache_opt: None,
             scratch: ShapeBuffer::default(),
         }
     }
@@ -387,10 +707,21 @@ impl Buffer {
         #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         let instant = std::time::Instant::now();
 
+        let options = LayoutOptions {
+            font_size: self.metrics.font_size,
+            width: self.width,
+            wrap: self.wrap,
+            tab_stops: &self.tab_stops,
+            tab_size: self.tab_size,
+            balance: self.wrap_balance,
+            default_align: self.align_opt,
+            overflow: self.overflow,
+            ellipsis: &self.ellipsis,
+        };
         for line in &mut self.lines {
             if line.shape_opt().is_some() {
                 line.reset_layout();
-                line.layout(font_system, self.metrics.font_size, self.width, self.wrap);
+                line.layout_in_buffer_with_options(&mut self.scratch, font_system, options);
             }
         }
 
@@ -405,8 +736,21 @@ impl Buffer {
         #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         let instant = std::time::Instant::now();
 
+        self.update_font_line_height(font_system);
+
         let mut reshaped = 0;
         let mut total_layout = 0;
+        let options = LayoutOptions {
+            font_size: self.metrics.font_size,
+            width: self.width,
+            wrap: self.wrap,
+            tab_stops: &self.tab_stops,
+            tab_size: self.tab_size,
+            balance: self.wrap_balance,
+            default_align: self.align_opt,
+            overflow: self.overflow,
+            ellipsis: &self.ellipsis,
+        };
         for line in &mut self.lines {
             if total_layout >= lines {
                 break;
@@ -415,13 +759,11 @@ impl Buffer {
             if line.shape_opt().is_none() {
                 reshaped += 1;
             }
-            let layout = line.layout_in_buffer(
-                &mut self.scratch,
-                font_system,
-                self.metrics.font_size,
-                self.width,
-                self.wrap,
-            );
+            if let Some(shape_cache) = &mut self.shape_cache_opt {
+                shape_cache.shape_line(line, &mut self.scratch, font_system);
+            }
+            let layout =
+                line.layout_in_buffer_with_options(&mut self.scratch, font_system, options);
             total_layout += layout.len() as i32;
         }
 
@@ -439,8 +781,21 @@ impl Buffer {
         #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         let instant = std::time::Instant::now();
 
+        self.update_font_line_height(font_system);
+
         let mut reshaped = 0;
         let mut layout_i = 0;
+        let options = LayoutOptions {
+            font_size: self.metrics.font_size,
+            width: self.width,
+            wrap: self.wrap,
+            tab_stops: &self.tab_stops,
+            tab_size: self.tab_size,
+            balance: self.wrap_balance,
+            default_align: self.align_opt,
+            overflow: self.overflow,
+            ellipsis: &self.ellipsis,
+        };
         for (line_i, line) in self.lines.iter_mut().enumerate() {
             if line_i > cursor.line {
                 break;
@@ -449,13 +804,11 @@ impl Buffer {
             if line.shape_opt().is_none() {
                 reshaped += 1;
             }
-            let layout = line.layout_in_buffer(
-                &mut self.scratch,
-                font_system,
-                self.metrics.font_size,
-                self.width,
-                self.wrap,
-            );
+            if let Some(shape_cache) = &mut self.shape_cache_opt {
+                shape_cache.shape_line(line, &mut self.scratch, font_system);
+            }
+            let layout =
+                line.layout_in_buffer_with_options(&mut self.scratch, font_system, options);
             if line_i == cursor.line {
                 let layout_cursor = self.layout_cursor(&cursor);
                 layout_i += layout_cursor.layout as i32;
@@ -471,11 +824,11 @@ impl Buffer {
             self.redraw = true;
         }
 
-        let lines = self.visible_lines();
+        let heights = self.layout_line_heights();
         if layout_i < self.scroll {
             self.scroll = layout_i;
-        } else if layout_i >= self.scroll + lines {
-            self.scroll = layout_i - (lines - 1);
+        } else if layout_i >= self.scroll + self.visible_lines_in(&heights, self.scroll) {
+            self.scroll = self.scroll_to_show(&heights, layout_i as usize);
         }
 
         self.shape_until_scroll(font_system);
@@ -486,9 +839,9 @@ impl Buffer {
         let lines = self.visible_lines();
 
         let scroll_end = self.scroll + lines;
-        let total_layout = self.shape_until(font_system, scroll_end);
+        self.shape_until(font_system, scroll_end);
 
-        self.scroll = cmp::max(0, cmp::min(total_layout - (lines - 1), self.scroll));
+        self.scroll = cmp::max(0, cmp::min(self.max_scroll(), self.scroll));
     }
 
     pub fn layout_cursor(&self, cursor: &Cursor) -> LayoutCursor {
@@ -528,7 +881,10 @@ impl Buffer {
         line_i: usize,
     ) -> Option<&ShapeLine> {
         let line = self.lines.get_mut(line_i)?;
-        Some(line.shape(font_system))
+        if let Some(shape_cache) = &mut self.shape_cache_opt {
+            shape_cache.shape_line(line, &mut self.scratch, font_system);
+        }
+        Some(line.shape_in_buffer(&mut self.scratch, font_system))
     }
 
     /// Lay out the provided line index and return the result
@@ -537,8 +893,363 @@ impl Buffer {
         font_system: &mut FontSystem,
         line_i: usize,
     ) -> Option<&[LayoutLine]> {
+        let options = LayoutOptions {
+            font_size: self.metrics.font_size,
+            width: self.width,
+            wrap: self.wrap,
+            tab_stops: &self.tab_stops,
+            tab_size: self.tab_size,
+            balance: self.wrap_balance,
+            default_align: self.align_opt,
+            overflow: self.overflow,
+            ellipsis: &self.ellipsis,
+        };
+        let line = self.lines.get_mut(line_i)?;
+        if let Some(shape_cache) = &mut self.shape_cache_opt {
+            shape_cache.shape_line(line, &mut self.scratch, font_system);
+        }
+        Some(line.layout_in_buffer_with_options(&mut self.scratch, font_system, options))
+    }
+
+    /// Get the number of lines that are shaped
+    pub fn shaped_line_count(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| line.shape_opt().is_some())
+            .count()
+    }
+
+    /// Get the number of lines that are not shaped yet
+    pub fn shape_pending_count(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| line.shape_opt().is_none())
+            .count()
+    }
+
+    /// Get the total number of glyphs in all laid out lines
+    pub fn total_glyph_count(&self) -> usize {
+        self.lines
+            .iter()
+            .filter_map(|line| line.layout_opt().as_ref())
+            .flat_map(|layout| layout.iter())
+            .map(|layout_line| layout_line.glyphs.len())
+            .sum()
+    }
+
+    /// Get the [`AttrsList`] of a [`BufferLine`]
+    pub fn line_attrs(&self, line_i: usize) -> Option<&AttrsList> {
+        self.lines.get(line_i).map(BufferLine::attrs_list)
+    }
+
+    /// Get the [`AttrsList`] of a [`BufferLine`], mutably
+    ///
+    /// This resets shape and layout of the line, as the attributes may change.
+    pub fn line_attrs_mut(&mut self, line_i: usize) -> Option<&mut AttrsList> {
         let line = self.lines.get_mut(line_i)?;
-        Some(line.layout(font_system, self.metrics.font_size, self.width, self.wrap))
+        self.redraw = true;
+        Some(line.attrs_list_mut())
+    }
+
+    /// Get the [`Attrs`] of the text at a [`Cursor`]
+    pub fn get_attrs_at(&self, cursor: Cursor) -> Attrs {
+        self.lines[cursor.line].attrs_list().get_span(cursor.index)
+    }
+
+    /// Get the x offset where the first layout line of a [`BufferLine`] starts
+    ///
+    /// This includes alignment and indentation. Returns 0.0 if the line is not laid out.
+    pub fn line_indent(&self, line_i: usize) -> f32 {
+        self.lines
+            .get(line_i)
+            .and_then(|line| line.layout_opt().as_ref())
+            .and_then(|layout| layout.first())
+            .and_then(|layout_line| {
+                layout_line
+                    .glyphs
+                    .iter()
+                    .map(|glyph| glyph.x)
+                    .reduce(f32::min)
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// Get the Unicode bidirectional analysis of a [`BufferLine`]
+    pub fn bidi_info_for_line(&self, line_i: usize) -> Option<BidiInfo> {
+        let line = self.lines.get(line_i)?;
+        let text = line.text();
+        let default_para_level = line.paragraph_style().direction.default_para_level();
+        let bidi = unicode_bidi::BidiInfo::new(text, default_para_level);
+
+        let levels = text
+            .char_indices()
+            .map(|(i, _)| bidi.levels[i].number())
+            .collect();
+
+        // Paragraphs are ordered one after another, each reordered on its own
+        let mut visual_order = Vec::new();
+        for para in bidi.paragraphs.iter() {
+            let (_, runs) = bidi.visual_runs(para, para.range.clone());
+            for run in runs {
+                let chars = text[run.clone()].char_indices().map(|(i, _)| run.start + i);
+                if bidi.levels[run.start].is_rtl() {
+                    visual_order.extend(chars.rev());
+                } else {
+                    visual_order.extend(chars);
+                }
+            }
+        }
+
+        let paragraph_level = match bidi.paragraphs.first() {
+            Some(para) => para.level.number(),
+            None => default_para_level.map_or(0, |level| level.number()),
+        };
+
+        Some(BidiInfo {
+            paragraph_level,
+            levels,
+            visual_order,
+        })
+    }
+
+    /// Get the byte indices in a [`BufferLine`] where it is soft wrapped into layout lines
+    ///
+    /// Returns an empty [`Vec`] if the line is not laid out.
+    pub fn soft_wrap_positions(&self, line_i: usize) -> Vec<usize> {
+        let layout = match self
+            .lines
+            .get(line_i)
+            .and_then(|line| line.layout_opt().as_ref())
+        {
+            Some(some) => some,
+            None => return Vec::new(),
+        };
+        layout
+            .iter()
+            .skip(1)
+            .filter_map(|layout_line| layout_line.glyphs.iter().map(|glyph| glyph.start).min())
+            .collect()
+    }
+
+    /// Get ranges of line indices grouped into paragraphs
+    ///
+    /// Each non-empty [`BufferLine`] is its own paragraph, and runs of adjacent empty lines are
+    /// collapsed into a single paragraph.
+    pub fn detect_paragraphs(&self) -> Vec<Range<usize>> {
+        let mut paragraphs: Vec<Range<usize>> = Vec::new();
+        for (line_i, line) in self.lines.iter().enumerate() {
+            if line.text().is_empty() {
+                if let Some(last) = paragraphs.last_mut() {
+                    if last.end == line_i && self.lines[last.start].text().is_empty() {
+                        last.end = line_i + 1;
+                        continue;
+                    }
+                }
+            }
+            paragraphs.push(line_i..line_i + 1);
+        }
+        paragraphs
+    }
+
+    /// Get the byte offset of the start of a line in the text of the whole buffer, where lines
+    /// are separated by `\n`
+    pub fn line_byte_offset(&self, line_i: usize) -> Option<usize> {
+        if line_i >= self.lines.len() {
+            return None;
+        }
+        Some(
+            self.lines[..line_i]
+                .iter()
+                .map(|line| line.text().len() + 1)
+                .sum(),
+        )
+    }
+
+    /// Get the byte range of a line in the text of the whole buffer, where lines are separated
+    /// by `\n`, not including the line ending
+    pub fn line_range(&self, line_i: usize) -> Option<Range<usize>> {
+        let start = self.line_byte_offset(line_i)?;
+        Some(start..start + self.lines[line_i].text().len())
+    }
+
+    /// Convert a byte offset in the text of the whole buffer, where lines are separated by `\n`,
+    /// to a [`Cursor`]
+    ///
+    /// Returns None if the offset is past the end of the text or not on a character boundary.
+    pub fn offset_to_cursor(&self, offset: usize) -> Option<Cursor> {
+        let mut line_start = 0;
+        for (line_i, line) in self.lines.iter().enumerate() {
+            let text = line.text();
+            if offset <= line_start + text.len() {
+                let index = offset - line_start;
+                return if text.is_char_boundary(index) {
+                    Some(Cursor::new(line_i, index))
+                } else {
+                    None
+                };
+            }
+            line_start += text.len() + 1;
+        }
+        None
+    }
+
+    /// Get the number of words in the buffer
+    ///
+    /// Words use the Unicode word boundaries, counting only words that contain an alphanumeric
+    /// character.
+    pub fn word_count(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|line| line.text().unicode_words().count())
+            .sum()
+    }
+
+    /// Get the number of characters (Unicode scalar values) in the buffer, including one `\n`
+    /// between each line
+    pub fn char_count(&self) -> usize {
+        let chars: usize = self
+            .lines
+            .iter()
+            .map(|line| line.text().chars().count())
+            .sum();
+        chars + self.lines.len().saturating_sub(1)
+    }
+
+    /// Replace the text from `start` to `end` with `text`, returning the cursor at the end of the
+    /// inserted text
+    ///
+    /// Lines in `text` are separated by `\n`. If `attrs_list` is None, the attributes of the
+    /// character before `start` are used. Only the changed lines are reset for shaping and layout.
+    /// The replaced lines are recorded as an undo step if history is enabled, see
+    /// [`Buffer::enable_history`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `start` or `end` is not a valid position in the buffer.
+    pub fn replace_range(
+        &mut self,
+        start: Cursor,
+        end: Cursor,
+        text: &str,
+        attrs_list: Option<AttrsList>,
+    ) -> Cursor {
+        let lines = cmp::min(start.line, end.line)..cmp::max(start.line, end.line) + 1;
+        self.record_lines(lines);
+        self.replace_range_unrecorded(start, end, text, attrs_list)
+    }
+
+    /// Replace the text from `start` to `end` like [`Buffer::replace_range`], without recording
+    /// history, for edits that are recorded by an [`Editor`](crate::Editor)
+    pub(crate) fn replace_range_unrecorded(
+        &mut self,
+        start: Cursor,
+        end: Cursor,
+        text: &str,
+        attrs_list: Option<AttrsList>,
+    ) -> Cursor {
+        let (start, end) = if (end.line, end.index) < (start.line, start.index) {
+            (end, start)
+        } else {
+            (start, end)
+        };
+
+        // Remove the replaced text, keeping the text after it
+        let after = self.lines[end.line].split_off(end.index);
+        self.lines.drain(start.line + 1..=end.line);
+        let line = &mut self.lines[start.line];
+        line.split_off(start.index);
+
+        let shaping = line.shaping();
+        let mut attrs_list = attrs_list.unwrap_or_else(|| {
+            AttrsList::new(line.attrs_list().get_span(start.index.saturating_sub(1)))
+        });
+
+        // Insert the new text, line by line
+        let mut line_i = start.line;
+        for (i, segment) in text.split('\n').enumerate() {
+            if i > 0 {
+                let len = self.lines[line_i].text().len();
+                let new_line = self.lines[line_i].split_off(len);
+                line_i += 1;
+                self.lines.insert(line_i, new_line);
+            }
+            let mut rest = attrs_list.split_off(segment.len());
+            let segment_attrs = mem::replace(&mut attrs_list, rest.split_off(1));
+            self.lines[line_i].append(BufferLine::new(segment, segment_attrs, shaping));
+        }
+
+        let cursor = Cursor::new(line_i, self.lines[line_i].text().len());
+        self.lines[line_i].append(after);
+        self.redraw = true;
+        cursor
+    }
+
+    /// Append `text` to the end of the buffer with the given attributes, joining it to the last
+    /// line and starting a new line after each `\n`
+    ///
+    /// Unlike [`Buffer::set_text`], the existing lines other than the last keep their shaping and
+    /// layout, so this is suitable for streaming text into the buffer. Each call is recorded as
+    /// one undo step if history is enabled, like [`Buffer::replace_range`].
+    pub fn append_text(&mut self, font_system: &mut FontSystem, text: &str, attrs_list: AttrsList) {
+        if self.lines.is_empty() {
+            self.lines.push(BufferLine::new(
+                String::new(),
+                AttrsList::new(attrs_list.defaults()),
+                Shaping::Advanced,
+            ));
+        }
+        let last_line = self.lines.len() - 1;
+        let end = Cursor::new(last_line, self.lines[last_line].text().len());
+        self.replace_range(end, end, text, Some(attrs_list));
+        self.shape_until_scroll(font_system);
+    }
+
+    /// Find all non-overlapping matches of `query`, returning the start and end of each match
+    ///
+    /// Text is compared one grapheme cluster at a time after NFC normalization, so matches
+    /// never split a combining sequence, and both ends of a match are valid cursors.
+    ///
+    /// Each line is searched on its own, so matches never span lines and a `query` containing
+    /// `\n` never matches.
+    pub fn search(&self, query: &str, case_sensitive: bool) -> Vec<(Cursor, Cursor)> {
+        let query = search_graphemes(query, case_sensitive);
+        let mut matches = Vec::new();
+        if query.is_empty() {
+            return matches;
+        }
+        for (line_i, line) in self.lines.iter().enumerate() {
+            search_line(line_i, line.text(), 0, &query, case_sensitive, &mut matches);
+        }
+        matches
+    }
+
+    /// Find the first match of `query` starting at or after `from`, see [`Buffer::search`]
+    pub fn search_next(
+        &self,
+        query: &str,
+        from: Cursor,
+        case_sensitive: bool,
+    ) -> Option<(Cursor, Cursor)> {
+        let query = search_graphemes(query, case_sensitive);
+        if query.is_empty() {
+            return None;
+        }
+        let mut matches = Vec::new();
+        for (line_i, line) in self.lines.iter().enumerate().skip(from.line) {
+            let from_index = if line_i == from.line { from.index } else { 0 };
+            search_line(
+                line_i,
+                line.text(),
+                from_index,
+                &query,
+                case_sensitive,
+                &mut matches,
+            );
+            if let Some(first) = matches.first() {
+                return Some(*first);
+            }
+        }
+        None
     }
 
     /// Get the current [`Metrics`]
@@ -574,56 +1285,523 @@ impl Buffer {
         }
     }
 
-    /// Get the current buffer dimensions (width, height)
-    pub fn size(&self) -> (f32, f32) {
-        (self.width, self.height)
+    /// Get the default [`Align`], see [`Buffer::set_align`]
+    pub fn align(&self) -> Option<Align> {
+        self.align_opt
     }
 
-    /// Set the current buffer dimensions
-    pub fn set_size(&mut self, font_system: &mut FontSystem, width: f32, height: f32) {
-        let clamped_width = width.max(0.0);
-        let clamped_height = height.max(0.0);
+    /// Set the default [`Align`] of lines
+    ///
+    /// This is used for lines that do not set an alignment with [`BufferLine::set_align`] or
+    /// with the [`Attrs`] at their start. Setting to None will use [`Align::Start`].
+    pub fn set_align(&mut self, font_system: &mut FontSystem, align: Option<Align>) {
+        if align != self.align_opt {
+            self.align_opt = align;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system);
+        }
+    }
 
-        if clamped_width != self.width || clamped_height != self.height {
-            self.width = clamped_width;
-            self.height = clamped_height;
+    /// Get the current [`Overflow`]
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Set the [`Overflow`] of lines that are wider than the buffer, which only applies when the
+    /// [`Wrap`] is [`Wrap::None`]
+    pub fn set_overflow(&mut self, font_system: &mut FontSystem, overflow: Overflow) {
+        if overflow != self.overflow {
+            self.overflow = overflow;
             self.relayout(font_system);
             self.shape_until_scroll(font_system);
         }
     }
 
-    /// Get the current scroll location
-    pub fn scroll(&self) -> i32 {
-        self.scroll
+    /// Get the string used by [`Overflow::Ellipsis`]
+    pub fn ellipsis_str(&self) -> &str {
+        &self.ellipsis
     }
 
-    /// Set the current scroll location
-    pub fn set_scroll(&mut self, scroll: i32) {
-        if scroll != self.scroll {
-            self.scroll = scroll;
-            self.redraw = true;
+    /// Set the string used by [`Overflow::Ellipsis`], which defaults to "…"
+    pub fn set_ellipsis_str(&mut self, font_system: &mut FontSystem, ellipsis: &str) {
+        if ellipsis != self.ellipsis {
+            self.ellipsis = ellipsis.to_string();
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system);
         }
     }
 
-    /// Get the number of lines that can be viewed in the buffer
-    pub fn visible_lines(&self) -> i32 {
-        (self.height / self.metrics.line_height) as i32
+    /// Get whether wrapped lines are balanced, see [`Buffer::set_wrap_balance`]
+    pub fn wrap_balance(&self) -> bool {
+        self.wrap_balance
     }
 
-    /// Set text of buffer, using provided attributes for each line by default
-    pub fn set_text(
-        &mut self,
-        font_system: &mut FontSystem,
-        text: &str,
-        attrs: Attrs,
-        shaping: Shaping,
-    ) {
-        self.set_rich_text(font_system, [(text, attrs)], shaping);
+    /// Set whether wrapped lines are balanced to more equal lengths, like CSS
+    /// `text-wrap: balance`, instead of filling each line before wrapping
+    pub fn set_wrap_balance(&mut self, font_system: &mut FontSystem, balance: bool) {
+        if balance != self.wrap_balance {
+            self.wrap_balance = balance;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system);
+        }
     }
 
-    /// Set text of buffer, using an iterator of styled spans (pairs of text and attributes)
+    /// Get the current tab size, in spaces
+    pub fn tab_size(&self) -> u8 {
+        self.tab_size
+    }
+
+    /// Set the tab size, in spaces
     ///
-    /// ```
+    /// During layout, each tab with no tab stop after it advances to the next multiple of
+    /// `tab_size` times the width of a space, measured from the start edge of the text of its
+    /// visual line, which is the right edge in right-to-left lines. A tab size of 0 keeps the
+    /// advance of the font. The default is 4.
+    pub fn set_tab_size(&mut self, font_system: &mut FontSystem, tab_size: u8) {
+        if tab_size != self.tab_size {
+            self.tab_size = tab_size;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system);
+        }
+    }
+
+    /// Get the current tab stops
+    pub fn tab_stops(&self) -> &[f32] {
+        &self.tab_stops
+    }
+
+    /// Set the tab stops, as sorted positions in pixels from the start edge of the text of each
+    /// layout line, which is the right edge in right-to-left lines
+    ///
+    /// During layout, each tab advances to the next tab stop after its position. Tabs with no
+    /// tab stop after them, or any tab if `stops` is empty, use the tab size, see
+    /// [`Buffer::set_tab_size`]. Tab advances are known while wrapping, so lines with tabs still
+    /// fit in the buffer width.
+    pub fn set_tab_stops(&mut self, font_system: &mut FontSystem, stops: Vec<f32>) {
+        if stops != self.tab_stops {
+            self.tab_stops = stops;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system);
+        }
+    }
+
+    /// Get the current buffer dimensions (width, height)
+    ///
+    /// The height is never less than [`Buffer::min_height`].
+    pub fn size(&self) -> (f32, f32) {
+        (self.width, self.height.max(self.min_height))
+    }
+
+    /// Get the current minimum height
+    pub fn min_height(&self) -> f32 {
+        self.min_height
+    }
+
+    /// Set the minimum height, so the buffer does not collapse when its height is smaller
+    pub fn set_min_height(&mut self, font_system: &mut FontSystem, min_height: f32) {
+        let clamped_min_height = min_height.max(0.0);
+        if clamped_min_height != self.min_height {
+            self.min_height = clamped_min_height;
+            self.redraw = true;
+            self.shape_until_scroll(font_system);
+        }
+    }
+
+    /// Get the size (width, height) of the whole buffer contents, ignoring scroll and the buffer
+    /// dimensions
+    ///
+    /// Lines that are not laid out yet are counted as [`Buffer::estimated_line_height`].
+    pub fn content_size_unclamped(&self) -> (f32, f32) {
+        let mut width: f32 = 0.0;
+        let mut height = 0.0;
+        for line in self.lines.iter() {
+            let line_height = self.line_height(line);
+            let paragraph_style = line.paragraph_style();
+            height += paragraph_style.spacing_before + paragraph_style.spacing_after;
+            match line.layout_opt() {
+                Some(layout) => {
+                    for layout_line in layout.iter() {
+                        width = width.max(paragraph_style.indent + layout_line.w);
+                        height += line_height;
+                    }
+                }
+                None => {
+                    height += self.estimated_line_height();
+                }
+            }
+        }
+        (width, height)
+    }
+
+    /// Get the natural size (width, height) of `text` laid out without wrapping, using the
+    /// current [`Metrics`] and tab stops
+    ///
+    /// The width is that of the widest line and the height is the sum of the line heights. This
+    /// does not change the text or layout of the buffer.
+    pub fn size_of_text(
+        &mut self,
+        font_system: &mut FontSystem,
+        text: &str,
+        attrs_list: AttrsList,
+    ) -> (f32, f32) {
+        let mut width: f32 = 0.0;
+        let mut height = 0.0;
+        let text_start = text.as_ptr() as usize;
+        for line_text in BidiParagraphs::new(text) {
+            let start = line_text.as_ptr() as usize - text_start;
+            let (_, line_attrs_list) = attrs_list.split_at(start);
+            let (line_attrs_list, _) = line_attrs_list.split_at(line_text.len());
+            let mut line = BufferLine::new(line_text, line_attrs_list, Shaping::Advanced);
+            let layout = line.layout_in_buffer_with_options(
+                &mut self.scratch,
+                font_system,
+                LayoutOptions {
+                    font_size: self.metrics.font_size,
+                    width: f32::MAX,
+                    wrap: Wrap::None,
+                    tab_stops: &self.tab_stops,
+                    tab_size: self.tab_size,
+                    balance: false,
+                    default_align: None,
+                    overflow: Overflow::Clip,
+                    ellipsis: "",
+                },
+            );
+            for layout_line in layout.iter() {
+                width = width.max(layout_line.w);
+            }
+            height += self.line_height(&line);
+        }
+        (width, height)
+    }
+
+    /// Set the current buffer dimensions
+    pub fn set_size(&mut self, font_system: &mut FontSystem, width: f32, height: f32) {
+        let clamped_width = width.max(0.0);
+        let clamped_height = height.max(0.0);
+
+        if clamped_width != self.width || clamped_height != self.height {
+            self.width = clamped_width;
+            self.height = clamped_height;
+            self.relayout(font_system);
+            self.shape_until_scroll(font_system);
+        }
+    }
+
+    /// Get the current scroll location
+    pub fn scroll(&self) -> i32 {
+        self.scroll
+    }
+
+    /// Set the current scroll location
+    pub fn set_scroll(&mut self, scroll: i32) {
+        if scroll != self.scroll {
+            self.scroll = scroll;
+            self.redraw = true;
+        }
+    }
+
+    /// Get the total number of layout lines
+    ///
+    /// Lines that are not laid out yet are estimated from [`Buffer::estimated_line_height`], and
+    /// count as at least one layout line.
+    pub fn layout_line_count(&self) -> i32 {
+        let estimated_layout_lines = self.estimated_layout_lines() as i32;
+        self.lines
+            .iter()
+            .map(|line| match line.layout_opt() {
+                Some(layout) => layout.len() as i32,
+                None => estimated_layout_lines,
+            })
+            .sum()
+    }
+
+    /// Get the number of layout lines counted for a line that is not laid out yet
+    fn estimated_layout_lines(&self) -> usize {
+        cmp::max(
+            1,
+            (self.estimated_line_height() / (self.metrics.line_height * self.line_spacing) + 0.5)
+                as usize,
+        )
+    }
+
+    /// Get the paragraph spacing before, the line height, and the paragraph spacing after every
+    /// layout line, as used by [`LayoutRunIter`]
+    ///
+    /// Lines that are not laid out yet are estimated like in [`Buffer::layout_line_count`].
+    fn layout_line_heights(&self) -> Vec<(f32, f32, f32)> {
+        let estimated_layout_lines = self.estimated_layout_lines();
+        let mut heights = Vec::new();
+        for line in self.lines.iter() {
+            let line_height = self.line_height(line);
+            let paragraph_style = line.paragraph_style();
+            let layout_len = line
+                .layout_opt()
+                .as_ref()
+                .map_or(estimated_layout_lines, |layout| layout.len());
+            for layout_i in 0..layout_len {
+                let spacing_before = if layout_i == 0 {
+                    paragraph_style.spacing_before
+                } else {
+                    0.0
+                };
+                let spacing_after = if layout_i + 1 == layout_len {
+                    paragraph_style.spacing_after
+                } else {
+                    0.0
+                };
+                heights.push((spacing_before, line_height, spacing_after));
+            }
+        }
+        heights
+    }
+
+    /// Count the layout lines from `scroll` that fit entirely within the buffer height
+    ///
+    /// Space after the last layout line is filled with lines of the default line height.
+    fn visible_lines_in(&self, heights: &[(f32, f32, f32)], scroll: i32) -> i32 {
+        let height = self.size().1;
+        let mut line_top = self.baseline_offset;
+        let mut lines = 0;
+        for &(spacing_before, line_height, spacing_after) in
+            heights.iter().skip(cmp::max(0, scroll) as usize)
+        {
+            line_top += spacing_before;
+            if line_height == 0.0 || line_top + line_height > height {
+                return lines;
+            }
+            line_top += line_height + spacing_after;
+            lines += 1;
+        }
+        let default_line_height = self.metrics.line_height * self.line_spacing;
+        lines + cmp::max(0, ((height - line_top) / default_line_height) as i32)
+    }
+
+    /// Get the smallest scroll that shows layout line `layout_i` entirely
+    fn scroll_to_show(&self, heights: &[(f32, f32, f32)], layout_i: usize) -> i32 {
+        let height = self.size().1;
+        let mut bottom = self.baseline_offset;
+        let mut scroll = layout_i;
+        for i in (0..=layout_i).rev() {
+            let (spacing_before, line_height, spacing_after) = match heights.get(i) {
+                Some(some) => *some,
+                None => break,
+            };
+            let mut line_extent = spacing_before + line_height;
+            if i != layout_i {
+                line_extent += spacing_after;
+            }
+            if i != layout_i && bottom + line_extent > height {
+                break;
+            }
+            bottom += line_extent;
+            scroll = i;
+        }
+        scroll as i32
+    }
+
+    /// Get the maximum number of layout lines, see [`Buffer::set_max_lines`]
+    pub fn max_lines(&self) -> Option<usize> {
+        self.max_lines_opt
+    }
+
+    /// Set the maximum number of layout lines returned by [`Buffer::layout_runs`], counted from
+    /// the start of the buffer, or None for no limit
+    ///
+    /// This limits the lines independently of the height of the buffer.
+    pub fn set_max_lines(&mut self, max_lines: Option<usize>) {
+        if max_lines != self.max_lines_opt {
+            self.max_lines_opt = max_lines;
+            self.redraw = true;
+        }
+    }
+
+    /// Check if there are more layout lines than allowed by [`Buffer::set_max_lines`]
+    ///
+    /// Lines that are not laid out yet count as one layout line.
+    pub fn exceeds_max_lines(&self) -> bool {
+        let max_lines = match self.max_lines_opt {
+            Some(some) => some,
+            None => return false,
+        };
+        let mut layout_lines = 0;
+        for line in self.lines.iter() {
+            layout_lines += line.layout_opt().as_ref().map_or(1, |layout| layout.len());
+            if layout_lines > max_lines {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Get the largest scroll location, in layout lines
+    ///
+    /// This is the smallest scroll at which the last layout line is entirely visible.
+    pub fn max_scroll(&self) -> i32 {
+        let heights = self.layout_line_heights();
+        match heights.len().checked_sub(1) {
+            Some(last) => self.scroll_to_show(&heights, last),
+            None => 0,
+        }
+    }
+
+    /// Get the current scroll location as a fraction of [`Buffer::max_scroll`], from 0.0 to 1.0
+    pub fn scroll_fraction(&self) -> f32 {
+        let max_scroll = self.max_scroll();
+        if max_scroll == 0 {
+            return 0.0;
+        }
+        (self.scroll as f32 / max_scroll as f32).clamp(0.0, 1.0)
+    }
+
+    /// Set the current scroll location as a fraction of [`Buffer::max_scroll`], from 0.0 to 1.0
+    pub fn set_scroll_fraction(&mut self, fraction: f32) {
+        let max_scroll = self.max_scroll() as f32;
+        self.set_scroll((fraction.clamp(0.0, 1.0) * max_scroll + 0.5) as i32);
+    }
+
+    /// Get the current baseline offset
+    pub fn baseline_offset(&self) -> f32 {
+        self.baseline_offset
+    }
+
+    /// Set the baseline offset, shifting all layout runs vertically by `offset` pixels
+    ///
+    /// This can be used to align the first baseline to an external baseline grid.
+    pub fn set_baseline_offset(&mut self, offset: f32) {
+        if offset != self.baseline_offset {
+            self.baseline_offset = offset;
+            self.redraw = true;
+        }
+    }
+
+    /// Get the current line spacing multiplier
+    pub fn line_spacing(&self) -> f32 {
+        self.line_spacing
+    }
+
+    /// Set the line spacing multiplier, where `1.0` is normal and `1.5` is one and a half line
+    /// height
+    ///
+    /// This is applied to the line height of every line, including lines with a line height
+    /// override.
+    pub fn set_line_spacing(&mut self, multiplier: f32) {
+        if multiplier != self.line_spacing {
+            self.line_spacing = multiplier;
+            self.redraw = true;
+        }
+    }
+
+    /// Get the line height used to lay out a [`BufferLine`] of this buffer
+    ///
+    /// This is the line height override of the line if set, or the line height of the current
+    /// [`Metrics`] otherwise, multiplied by the line spacing.
+    pub fn line_height(&self, line: &BufferLine) -> f32 {
+        line.line_height_override()
+            .unwrap_or(self.metrics.line_height)
+            * self.line_spacing
+    }
+
+    /// Get the estimated height of lines that are not laid out yet
+    ///
+    /// This defaults to the ascent, descent, and line gap of the font matching the default
+    /// [`Attrs`] of the first line, at the font size of the current [`Metrics`], multiplied by the
+    /// line spacing. Until the buffer is shaped, the line height of the [`Metrics`] is used
+    /// instead.
+    pub fn estimated_line_height(&self) -> f32 {
+        self.estimated_line_height_opt.unwrap_or_else(|| {
+            self.font_line_height_opt
+                .unwrap_or(self.metrics.line_height)
+                * self.line_spacing
+        })
+    }
+
+    /// Update the line height of the font matching the default [`Attrs`] of the first line, if
+    /// the font size, those attributes, or the fonts changed since it was last computed
+    fn update_font_line_height(&mut self, font_system: &mut FontSystem) {
+        let attrs = self
+            .lines
+            .first()
+            .map_or_else(Attrs::new, |line| line.attrs_list().defaults());
+        let db_generation = font_system.db_generation();
+        if let Some((font_size, key_attrs, key_generation)) = &self.font_line_height_key_opt {
+            if *font_size == self.metrics.font_size
+                && *key_generation == db_generation
+                && key_attrs.as_attrs() == attrs
+            {
+                return;
+            }
+        }
+
+        let font_matches = font_system.get_font_matches(attrs.clone());
+        self.font_line_height_opt = font_matches
+            .iter()
+            .find_map(|id| font_system.get_font(*id))
+            .and_then(|font| font.line_height_px(self.metrics.font_size));
+        self.font_line_height_key_opt = Some((
+            self.metrics.font_size,
+            AttrsOwned::new(attrs),
+            db_generation,
+        ));
+    }
+
+    /// Set the estimated height of lines that are not laid out yet, used for scroll and content
+    /// size computations, or None to use the default
+    pub fn set_estimated_line_height(&mut self, height_opt: Option<f32>) {
+        if height_opt != self.estimated_line_height_opt {
+            self.estimated_line_height_opt = height_opt;
+            self.redraw = true;
+        }
+    }
+
+    /// Get the number of layout lines that can be viewed in the buffer at the current scroll
+    ///
+    /// This uses the same line heights and paragraph spacing as [`Buffer::layout_runs`].
+    pub fn visible_lines(&self) -> i32 {
+        self.visible_lines_in(&self.layout_line_heights(), self.scroll)
+    }
+
+    /// Set text of buffer, using provided attributes for each line by default
+    pub fn set_text(
+        &mut self,
+        font_system: &mut FontSystem,
+        text: &str,
+        attrs: Attrs,
+        shaping: Shaping,
+    ) {
+        self.set_rich_text(font_system, [(text, attrs)], shaping);
+    }
+
+    /// Set text of buffer, splitting lines only on the line ending given by `newline`
+    ///
+    /// Unlike [`Buffer::set_text`], other line endings and paragraph separators are kept in the
+    /// text of each line.
+    pub fn set_text_with_newline_behavior(
+        &mut self,
+        font_system: &mut FontSystem,
+        text: &str,
+        attrs: Attrs,
+        shaping: Shaping,
+        newline: NewlineBehavior,
+    ) {
+        self.record_history();
+        self.lines.clear();
+        for line in text.split(newline.separator(text)) {
+            self.lines.push(BufferLine::new(
+                line,
+                AttrsList::new(attrs.clone()),
+                shaping,
+            ));
+        }
+
+        self.scroll = 0;
+
+        self.shape_until_scroll(font_system);
+    }
+
+    /// Set text of buffer, using an iterator of styled spans (pairs of text and attributes)
+    ///
+    /// ```
     /// # use cosmic_text::{Attrs, Buffer, Family, FontSystem, Metrics, Shaping};
     /// # let mut font_system = FontSystem::new();
     /// let mut buffer = Buffer::new_empty(Metrics::new(32.0, 44.0));
@@ -631,7 +1809,7 @@ impl Buffer {
     /// buffer.set_rich_text(
     ///     &mut font_system,
     ///     [
-    ///         ("hello, ", attrs),
+    ///         ("hello, ", attrs.clone()),
     ///         ("cosmic\ntext", attrs.family(Family::Monospace)),
     ///     ],
     ///     Shaping::Advanced,
@@ -645,6 +1823,7 @@ impl Buffer {
     ) where
         I: IntoIterator<Item = (&'s str, Attrs<'r>)>,
     {
+        self.record_history();
         self.lines.clear();
 
         let mut attrs_list = AttrsList::new(Attrs::new());
@@ -690,7 +1869,7 @@ impl Buffer {
                 let text_start = line_string.len();
                 line_string.push_str(text);
                 let text_end = line_string.len();
-                attrs_list.add_span(text_start..text_end, *attrs);
+                attrs_list.add_span(text_start..text_end, attrs.clone());
             }
 
             // we know that at the end of a line,
@@ -733,18 +1912,226 @@ impl Buffer {
         self.redraw = redraw;
     }
 
+    /// Start recording history for [`Buffer::undo`] and [`Buffer::redo`], keeping up to
+    /// `max_depth` undo steps
+    ///
+    /// [`Buffer::set_text`], [`Buffer::set_rich_text`], [`Buffer::replace_range`], and
+    /// [`Buffer::append_text`] are recorded automatically, keeping only the lines they replace.
+    /// Call [`Buffer::record_history`] before modifying [`Buffer::lines`] directly.
+    ///
+    /// This is meant for using a [`Buffer`] without an [`Editor`](crate::Editor), which keeps its
+    /// own history. Edits made by an [`Editor`](crate::Editor) are not recorded here.
+    pub fn enable_history(&mut self, max_depth: usize) {
+        self.history_opt = Some(BufferHistory {
+            max_depth,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        });
+    }
+
+    /// Stop recording history, discarding all undo and redo steps
+    pub fn disable_history(&mut self) {
+        self.history_opt = None;
+    }
+
+    /// Record all current lines as an undo step, if history is enabled
+    ///
+    /// Nothing is recorded while the buffer has no lines.
+    pub fn record_history(&mut self) {
+        if self.lines.is_empty() {
+            return;
+        }
+        self.record_lines(0..self.lines.len());
+    }
+
+    /// Undo the last recorded edit, returning true if there was one
+    pub fn undo(&mut self, font_system: &mut FontSystem) -> bool {
+        let edit = match self
+            .history_opt
+            .as_mut()
+            .and_then(|history| history.undo.pop_back())
+        {
+            Some(edit) => edit,
+            None => return false,
+        };
+        let redo = match self.apply_edit(font_system, edit) {
+            Some(redo) => redo,
+            None => return false,
+        };
+        if let Some(history) = &mut self.history_opt {
+            history.redo.push(redo);
+        }
+        true
+    }
+
+    /// Redo the last undone edit, returning true if there was one
+    pub fn redo(&mut self, font_system: &mut FontSystem) -> bool {
+        let edit = match self
+            .history_opt
+            .as_mut()
+            .and_then(|history| history.redo.pop())
+        {
+            Some(edit) => edit,
+            None => return false,
+        };
+        let undo = match self.apply_edit(font_system, edit) {
+            Some(undo) => undo,
+            None => return false,
+        };
+        if let Some(history) = &mut self.history_opt {
+            history.undo.push_back(undo);
+        }
+        true
+    }
+
+    /// Enable caching of shaped lines, keeping up to `capacity` shapes and evicting the least
+    /// recently used
+    ///
+    /// Lines with the same text, attributes, shaping, and direction will share a cached shape
+    /// instead of being shaped again. This is useful when many lines repeat the same content.
+    pub fn enable_shape_cache(&mut self, capacity: usize) {
+        self.shape_cache_opt = Some(ShapeCache::new(capacity));
+    }
+
+    /// Disable caching of shaped lines, dropping all cached shapes
+    pub fn disable_shape_cache(&mut self) {
+        self.shape_cache_opt = None;
+    }
+
+    /// Record `lines` as an undo step before they are replaced, if history is enabled
+    fn record_lines(&mut self, lines: Range<usize>) {
+        if let Some(history) = &mut self.history_opt {
+            if history.max_depth == 0 {
+                return;
+            }
+            history.redo.clear();
+            history.undo.push_back(BufferEdit {
+                start: lines.start,
+                after: self.lines.len() - lines.end,
+                lines: self.lines[lines]
+                    .iter()
+                    .map(BufferLine::clone_unshaped)
+                    .collect(),
+            });
+            if history.undo.len() > history.max_depth {
+                history.undo.pop_front();
+            }
+        }
+    }
+
+    /// Put back the lines of `edit`, returning the lines they replaced as the opposite edit
+    ///
+    /// Returns None if the lines of the buffer were changed without being recorded, so that
+    /// `edit` no longer fits.
+    fn apply_edit(&mut self, font_system: &mut FontSystem, edit: BufferEdit) -> Option<BufferEdit> {
+        let end = self.lines.len().checked_sub(edit.after)?;
+        if edit.start > end {
+            return None;
+        }
+        let lines = self
+            .lines
+            .splice(edit.start..end, edit.lines)
+            .map(|mut line| {
+                line.reset();
+                line
+            })
+            .collect();
+        self.redraw = true;
+        self.shape_until_scroll(font_system);
+        Some(BufferEdit {
+            start: edit.start,
+            after: edit.after,
+            lines,
+        })
+    }
+
     /// Get the visible layout runs for rendering and other tasks
     pub fn layout_runs(&self) -> LayoutRunIter {
         LayoutRunIter::new(self)
     }
 
+    /// Get an iterator over the index and [`BufferLine`] of every line
+    pub fn iter_lines(&self) -> Enumerate<slice::Iter<'_, BufferLine>> {
+        self.lines.iter().enumerate()
+    }
+
+    /// Get a mutable iterator over the index and [`BufferLine`] of every line
+    ///
+    /// Lines that are modified must be shaped again, for example by [`Buffer::shape_until_scroll`].
+    pub fn iter_lines_mut(&mut self) -> Enumerate<slice::IterMut<'_, BufferLine>> {
+        self.redraw = true;
+        self.lines.iter_mut().enumerate()
+    }
+
+    /// Get the index and [`ShapeLine`] of every line that is already shaped, without shaping
+    pub fn shaped_lines(&self) -> impl Iterator<Item = (usize, &ShapeLine)> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(line_i, line)| line.shape_opt().map(|shape| (line_i, shape)))
+    }
+
+    /// Get the bounding box of each visible [`BufferLine`] (or paragraph)
+    ///
+    /// Each box spans the full width of the buffer, from the top of the first visible layout run
+    /// of the line to the bottom of the last one.
+    pub fn paragraph_bounding_boxes(&self) -> Vec<Rect> {
+        let mut boxes: Vec<Rect> = Vec::new();
+        let mut last_line_i = None;
+        for run in self.layout_runs() {
+            if last_line_i == Some(run.line_i) {
+                if let Some(rect) = boxes.last_mut() {
+                    rect.h = run.line_top + run.line_height - rect.y;
+                }
+            } else {
+                boxes.push(Rect::new(0.0, run.line_top, self.width, run.line_height));
+                last_line_i = Some(run.line_i);
+            }
+        }
+        boxes
+    }
+
+    /// Get the bounding rectangle of the glyph at `cursor`, relative to the buffer, see
+    /// [`LayoutRun::glyph_bounds`]
+    ///
+    /// Returns None if the glyph is not visible, or if the cursor is at the end of the line.
+    pub fn char_bounds(&self, cursor: Cursor) -> Option<Rect> {
+        for run in self.layout_runs() {
+            if run.line_i != cursor.line {
+                continue;
+            }
+            for glyph in run.glyphs.iter() {
+                if glyph.start <= cursor.index && cursor.index < glyph.end {
+                    return Some(run.glyph_bounds(glyph));
+                }
+            }
+        }
+        None
+    }
+
+    /// Get the start and end of the word containing `cursor`, using the Unicode word boundaries
+    /// of the line
+    ///
+    /// Returns None if the cursor is not in a word, for example if it is on whitespace.
+    pub fn word_at_cursor(&self, cursor: Cursor) -> Option<(Cursor, Cursor)> {
+        let text = self.lines.get(cursor.line)?.text();
+        text.unicode_word_indices()
+            .map(|(i, word)| (i, i + word.len()))
+            .find(|&(start, end)| cursor.index >= start && cursor.index < end)
+            .map(|(start, end)| {
+                (
+                    Cursor::new_with_affinity(cursor.line, start, Affinity::After),
+                    Cursor::new_with_affinity(cursor.line, end, Affinity::Before),
+                )
+            })
+    }
+
     /// Convert x, y position to Cursor (hit detection)
     pub fn hit(&self, x: f32, y: f32) -> Option<Cursor> {
         #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
         let instant = std::time::Instant::now();
 
         let font_size = self.metrics.font_size;
-        let line_height = self.metrics.line_height;
 
         let mut new_cursor_opt = None;
 
@@ -757,7 +2144,7 @@ impl Buffer {
                 first_run = false;
                 let new_cursor = Cursor::new(run.line_i, 0);
                 new_cursor_opt = Some(new_cursor);
-            } else if y >= line_y - font_size && y < line_y - font_size + line_height {
+            } else if y >= line_y - font_size && y < line_y - font_size + run.line_height {
                 let mut new_cursor_glyph = run.glyphs.len();
                 let mut new_cursor_char = 0;
                 let mut new_cursor_affinity = Affinity::After;
@@ -839,6 +2226,23 @@ impl Buffer {
         new_cursor_opt
     }
 
+    /// Get the [`Font`] that was used to shape a glyph of a [`LayoutRun`]
+    ///
+    /// This allows querying per-glyph font metrics without shaping the line again. The font is
+    /// looked up by [`LayoutGlyph::font_id`], and `None` is returned if `glyph` is not one of the
+    /// glyphs of `run` or the font is no longer loaded.
+    pub fn font_for_glyph(
+        &self,
+        font_system: &mut FontSystem,
+        run: &LayoutRun,
+        glyph: &LayoutGlyph,
+    ) -> Option<Arc<Font>> {
+        if !run.glyphs.iter().any(|run_glyph| ptr::eq(run_glyph, glyph)) {
+            return None;
+        }
+        font_system.get_font(glyph.font_id)
+    }
+
     /// Draw the buffer
     #[cfg(feature = "swash")]
     pub fn draw<F>(
@@ -851,6 +2255,8 @@ impl Buffer {
         F: FnMut(i32, i32, u32, u32, Color),
     {
         for run in self.layout_runs() {
+            draw_backgrounds(&run, &mut f);
+
             for glyph in run.glyphs.iter() {
                 let physical_glyph = glyph.physical((0., 0.), 1.0);
 
@@ -874,10 +2280,204 @@ impl Buffer {
                     },
                 );
             }
+
+            draw_decorations(font_system, &run, color, &mut f);
         }
     }
 }
 
+/// Normalize a grapheme cluster for comparison in [`Buffer::search`]
+fn search_key(grapheme: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        grapheme.nfc().collect()
+    } else {
+        grapheme.nfc().flat_map(char::to_lowercase).collect()
+    }
+}
+
+/// Split `text` into normalized grapheme clusters for [`Buffer::search`]
+fn search_graphemes(text: &str, case_sensitive: bool) -> Vec<String> {
+    text.graphemes(true)
+        .map(|grapheme| search_key(grapheme, case_sensitive))
+        .collect()
+}
+
+/// Add non-overlapping matches of `query` in `text` starting at or after `from_index`
+fn search_line(
+    line_i: usize,
+    text: &str,
+    from_index: usize,
+    query: &[String],
+    case_sensitive: bool,
+    matches: &mut Vec<(Cursor, Cursor)>,
+) {
+    let graphemes: Vec<(usize, usize, String)> = text
+        .grapheme_indices(true)
+        .map(|(i, grapheme)| (i, i + grapheme.len(), search_key(grapheme, case_sensitive)))
+        .collect();
+
+    let mut i = 0;
+    while i + query.len() <= graphemes.len() {
+        let candidate = &graphemes[i..i + query.len()];
+        if candidate[0].0 >= from_index
+            && candidate
+                .iter()
+                .zip(query)
+                .all(|(grapheme, key)| grapheme.2 == *key)
+        {
+            matches.push((
+                Cursor::new(line_i, candidate[0].0),
+                Cursor::new(line_i, candidate[query.len() - 1].1),
+            ));
+            i += query.len();
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Draw the backgrounds of contiguous glyphs with the same background color in a [`LayoutRun`]
+#[cfg(feature = "swash")]
+pub(crate) fn draw_backgrounds<F>(run: &LayoutRun, f: &mut F)
+where
+    F: FnMut(i32, i32, u32, u32, Color),
+{
+    // Background segment as (x_start, x_end, color)
+    let mut segment_opt: Option<(f32, f32, Color)> = None;
+    for glyph_opt in run.glyphs.iter().map(Some).chain(core::iter::once(None)) {
+        let glyph_segment_opt = glyph_opt.and_then(|glyph| {
+            glyph
+                .background_opt
+                .map(|background| (glyph.x, glyph.x + glyph.w, background))
+        });
+
+        segment_opt = match (segment_opt.take(), glyph_segment_opt) {
+            (Some(segment), Some(glyph_segment)) if segment.2 == glyph_segment.2 => Some((
+                segment.0.min(glyph_segment.0),
+                segment.1.max(glyph_segment.1),
+                segment.2,
+            )),
+            (segment_opt, glyph_segment_opt) => {
+                if let Some((x_start, x_end, background)) = segment_opt {
+                    f(
+                        x_start as i32,
+                        run.line_top as i32,
+                        (x_end - x_start) as u32,
+                        run.line_height as u32,
+                        background,
+                    );
+                }
+                glyph_segment_opt
+            }
+        };
+    }
+}
+
+/// Draw the decorations of contiguous decorated glyphs with the same color in a [`LayoutRun`]
+#[cfg(feature = "swash")]
+pub(crate) fn draw_decorations<F>(
+    font_system: &mut FontSystem,
+    run: &LayoutRun,
+    color: Color,
+    f: &mut F,
+) where
+    F: FnMut(i32, i32, u32, u32, Color),
+{
+    for decoration in [
+        TextDecoration::UNDERLINE,
+        TextDecoration::STRIKETHROUGH,
+        TextDecoration::OVERLINE,
+    ] {
+        // Decoration segment as (x_start, x_end, color, first glyph)
+        let mut segment_opt: Option<(f32, f32, Color, &LayoutGlyph)> = None;
+        for glyph_opt in run.glyphs.iter().map(Some).chain(core::iter::once(None)) {
+            let glyph_segment_opt = glyph_opt
+                .filter(|glyph| glyph.text_decoration.contains(decoration))
+                .map(|glyph| {
+                    (
+                        glyph.x,
+                        glyph.x + glyph.w,
+                        glyph
+                            .decoration_color_opt
+                            .or(glyph.color_opt)
+                            .unwrap_or(color),
+                        glyph,
+                    )
+                });
+
+            segment_opt = match (segment_opt.take(), glyph_segment_opt) {
+                (Some(segment), Some(glyph_segment)) if segment.2 == glyph_segment.2 => Some((
+                    segment.0.min(glyph_segment.0),
+                    segment.1.max(glyph_segment.1),
+                    segment.2,
+                    segment.3,
+                )),
+                (segment_opt, glyph_segment_opt) => {
+                    if let Some((x_start, x_end, segment_color, glyph)) = segment_opt {
+                        let (offset, thickness) =
+                            decoration_metrics(font_system, glyph, decoration);
+                        f(
+                            x_start as i32,
+                            (run.line_y + offset) as i32,
+                            (x_end - x_start) as u32,
+                            cmp::max(1, thickness as u32),
+                            segment_color,
+                        );
+                    }
+                    glyph_segment_opt
+                }
+            };
+        }
+    }
+}
+
+/// Get the offset from the baseline to the top of a decoration line, and its thickness, using
+/// the metrics of the font of `glyph`
+#[cfg(feature = "swash")]
+fn decoration_metrics(
+    font_system: &mut FontSystem,
+    glyph: &LayoutGlyph,
+    decoration: TextDecoration,
+) -> (f32, f32) {
+    let font = match font_system.get_font(glyph.font_id) {
+        Some(some) => some,
+        None => {
+            let offset = match decoration {
+                TextDecoration::UNDERLINE => glyph.font_size / 10.0,
+                TextDecoration::STRIKETHROUGH => -glyph.font_size / 4.0,
+                _ => -glyph.font_size,
+            };
+            return (offset, 1.0);
+        }
+    };
+    let face = font.rustybuzz();
+    let scale = glyph.font_size / face.units_per_em() as f32;
+    let underline_opt = face.underline_metrics();
+    let underline_thickness = underline_opt.map_or(1.0, |metrics| metrics.thickness as f32 * scale);
+    match decoration {
+        TextDecoration::UNDERLINE => match underline_opt {
+            Some(metrics) => (
+                -(metrics.position as f32) * scale,
+                metrics.thickness as f32 * scale,
+            ),
+            None => (glyph.font_size / 10.0, underline_thickness),
+        },
+        TextDecoration::STRIKETHROUGH => match face.strikeout_metrics() {
+            Some(metrics) => (
+                -(metrics.position as f32) * scale,
+                metrics.thickness as f32 * scale,
+            ),
+            None => {
+                let x_height = face
+                    .x_height()
+                    .map_or(glyph.font_size / 2.0, |x_height| x_height as f32 * scale);
+                (-x_height / 2.0, underline_thickness)
+            }
+        },
+        _ => (-(face.ascender() as f32) * scale, underline_thickness),
+    }
+}
+
 impl<'a> BorrowedWithFontSystem<'a, Buffer> {
     /// Pre-shape lines in the buffer, up to `lines`, return actual number of layout lines
     pub fn shape_until(&mut self, lines: i32) -> i32 {
@@ -918,16 +2518,73 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
         self.inner.set_wrap(self.font_system, wrap);
     }
 
+    /// Set the default [`Align`] of lines
+    pub fn set_align(&mut self, align: Option<Align>) {
+        self.inner.set_align(self.font_system, align);
+    }
+
+    /// Set the [`Overflow`] of lines that are wider than the buffer
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.inner.set_overflow(self.font_system, overflow);
+    }
+
+    /// Set the string used by [`Overflow::Ellipsis`]
+    pub fn set_ellipsis_str(&mut self, ellipsis: &str) {
+        self.inner.set_ellipsis_str(self.font_system, ellipsis);
+    }
+
+    /// Set whether wrapped lines are balanced to more equal lengths
+    pub fn set_wrap_balance(&mut self, balance: bool) {
+        self.inner.set_wrap_balance(self.font_system, balance);
+    }
+
+    /// Set the tab size, in spaces
+    pub fn set_tab_size(&mut self, tab_size: u8) {
+        self.inner.set_tab_size(self.font_system, tab_size);
+    }
+
+    /// Set the tab stops, as sorted absolute x positions in pixels
+    pub fn set_tab_stops(&mut self, stops: Vec<f32>) {
+        self.inner.set_tab_stops(self.font_system, stops);
+    }
+
+    /// Get the natural size (width, height) of `text` laid out without wrapping
+    pub fn size_of_text(&mut self, text: &str, attrs_list: AttrsList) -> (f32, f32) {
+        self.inner.size_of_text(self.font_system, text, attrs_list)
+    }
+
     /// Set the current buffer dimensions
     pub fn set_size(&mut self, width: f32, height: f32) {
         self.inner.set_size(self.font_system, width, height);
     }
 
+    /// Set the minimum height, so the buffer does not collapse when its height is smaller
+    pub fn set_min_height(&mut self, min_height: f32) {
+        self.inner.set_min_height(self.font_system, min_height);
+    }
+
     /// Set text of buffer, using provided attributes for each line by default
     pub fn set_text(&mut self, text: &str, attrs: Attrs, shaping: Shaping) {
         self.inner.set_text(self.font_system, text, attrs, shaping);
     }
 
+    /// Append `text` to the end of the buffer with the given attributes
+    pub fn append_text(&mut self, text: &str, attrs_list: AttrsList) {
+        self.inner.append_text(self.font_system, text, attrs_list);
+    }
+
+    /// Set text of buffer, splitting lines only on the line ending given by `newline`
+    pub fn set_text_with_newline_behavior(
+        &mut self,
+        text: &str,
+        attrs: Attrs,
+        shaping: Shaping,
+        newline: NewlineBehavior,
+    ) {
+        self.inner
+            .set_text_with_newline_behavior(self.font_system, text, attrs, shaping, newline);
+    }
+
     /// Set text of buffer, using an iterator of styled spans (pairs of text and attributes)
     ///
     /// ```
@@ -938,7 +2595,7 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
     /// let attrs = Attrs::new().family(Family::Serif);
     /// buffer.set_rich_text(
     ///     [
-    ///         ("hello, ", attrs),
+    ///         ("hello, ", attrs.clone()),
     ///         ("cosmic\ntext", attrs.family(Family::Monospace)),
     ///     ],
     ///     Shaping::Advanced,
@@ -951,6 +2608,16 @@ impl<'a> BorrowedWithFontSystem<'a, Buffer> {
         self.inner.set_rich_text(self.font_system, spans, shaping);
     }
 
+    /// Restore the lines of the last undo step, returning true if there was one
+    pub fn undo(&mut self) -> bool {
+        self.inner.undo(self.font_system)
+    }
+
+    /// Restore the lines of the last redo step, returning true if there was one
+    pub fn redo(&mut self) -> bool {
+        self.inner.redo(self.font_system)
+    }
+
     /// Draw the buffer
     #[cfg(feature = "swash")]
     pub fn draw<F>(&mut self, cache: &mut crate::SwashCache, color: Color, f: F)
@@ -1,7 +1,42 @@
+use alloc::sync::Arc;
 #[cfg(not(feature = "std"))]
 use alloc::{string::String, vec::Vec};
+use core::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
 
-use crate::{Align, AttrsList, FontSystem, LayoutLine, ShapeBuffer, ShapeLine, Shaping, Wrap};
+use crate::{
+    Align, AttrsList, BaseDirection, Buffer, FontSystem, LayoutLine, Overflow, ParagraphStyle,
+    ShapeBuffer, ShapeLine, Shaping, Wrap,
+};
+
+/// Number of spaces in the width of a tab, if not set with [`Buffer::set_tab_size`]
+pub(crate) const DEFAULT_TAB_SIZE: u8 = 4;
+
+/// Settings of a [`Buffer`] used to lay out each of its lines, see
+/// [`BufferLine::layout_in_buffer_with_options`]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LayoutOptions<'a> {
+    pub font_size: f32,
+    pub width: f32,
+    pub wrap: Wrap,
+    /// Tab stops, see [`Buffer::set_tab_stops`]
+    pub tab_stops: &'a [f32],
+    /// Number of spaces in the width of a tab after the last tab stop
+    pub tab_size: u8,
+    /// Lay out wrapped lines in the narrowest width that does not add more lines, so their
+    /// lengths are more equal
+    pub balance: bool,
+    /// Alignment used if neither the line nor the [`Attrs`] at its start set one
+    ///
+    /// [`Attrs`]: crate::Attrs
+    pub default_align: Option<Align>,
+    /// Overflow of lines that are not wrapped
+    pub overflow: Overflow,
+    /// Text at the end of lines truncated by [`Overflow::Ellipsis`]
+    pub ellipsis: &'a str,
+}
 
 /// A line (or paragraph) of text that is shaped and laid out
 #[derive(Debug)]
@@ -11,7 +46,9 @@ pub struct BufferLine {
     attrs_list: AttrsList,
     wrap: Wrap,
     align: Option<Align>,
-    shape_opt: Option<ShapeLine>,
+    line_height_opt: Option<f32>,
+    paragraph_style: ParagraphStyle,
+    shape_opt: Option<Arc<ShapeLine>>,
     layout_opt: Option<Vec<LayoutLine>>,
     shaping: Shaping,
 }
@@ -26,6 +63,8 @@ impl BufferLine {
             attrs_list,
             wrap: Wrap::Word,
             align: None,
+            line_height_opt: None,
+            paragraph_style: ParagraphStyle::default(),
             shape_opt: None,
             layout_opt: None,
             shaping,
@@ -54,6 +93,14 @@ impl BufferLine {
         }
     }
 
+    /// Get the byte range of this line in the text of the whole `buffer`, where this line is at
+    /// index `line_i` and lines are separated by a single `\n`
+    ///
+    /// Returns None if `line_i` is not a line of `buffer`. See [`Buffer::line_range`].
+    pub fn byte_range_in_buffer(&self, line_i: usize, buffer: &Buffer) -> Option<Range<usize>> {
+        buffer.line_range(line_i)
+    }
+
     /// Consume this line, returning only its text contents as a String.
     pub fn into_text(self) -> String {
         self.text
@@ -64,6 +111,14 @@ impl BufferLine {
         &self.attrs_list
     }
 
+    /// Get attributes list, mutably
+    ///
+    /// This resets shape and layout, as the attributes may change.
+    pub fn attrs_list_mut(&mut self) -> &mut AttrsList {
+        self.reset();
+        &mut self.attrs_list
+    }
+
     /// Set attributes list
     ///
     /// Will reset shape and layout if it differs from current attributes list.
@@ -78,6 +133,11 @@ impl BufferLine {
         }
     }
 
+    /// Get the [`Shaping`] strategy
+    pub fn shaping(&self) -> Shaping {
+        self.shaping
+    }
+
     /// Get wrapping setting (wrap by characters/words or no wrapping)
     pub fn wrap(&self) -> Wrap {
         self.wrap
@@ -117,6 +177,51 @@ impl BufferLine {
         }
     }
 
+    /// Get the line height override
+    pub fn line_height_override(&self) -> Option<f32> {
+        self.line_height_opt
+    }
+
+    /// Set the line height override
+    ///
+    /// Setting to `Some(height)` replaces the line height from the buffer [`Metrics`] for every
+    /// layout line of this line. Setting to None will use the buffer [`Metrics`].
+    /// Returns true if the line height override changed
+    ///
+    /// [`Metrics`]: crate::Metrics
+    pub fn set_line_height_override(&mut self, height: Option<f32>) -> bool {
+        if height != self.line_height_opt {
+            self.line_height_opt = height;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get the [`ParagraphStyle`]
+    pub fn paragraph_style(&self) -> ParagraphStyle {
+        self.paragraph_style
+    }
+
+    /// Set the [`ParagraphStyle`]
+    ///
+    /// Will reset shape and layout if the direction differs from the current paragraph style,
+    /// otherwise will reset layout if it differs from the current paragraph style.
+    /// Returns true if the line was reset
+    pub fn set_paragraph_style(&mut self, style: ParagraphStyle) -> bool {
+        if style != self.paragraph_style {
+            if style.direction != self.paragraph_style.direction {
+                self.reset();
+            } else {
+                self.reset_layout();
+            }
+            self.paragraph_style = style;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Append line at end of this line
     ///
     /// The wrap setting of the appended line will be lost
@@ -147,6 +252,18 @@ impl BufferLine {
 
         let mut new = Self::new(text, attrs_list, self.shaping);
         new.wrap = self.wrap;
+        new.line_height_opt = self.line_height_opt;
+        new.paragraph_style = self.paragraph_style;
+        new
+    }
+
+    /// Copy the text, attributes, and styles of this line, without shaping and layout information
+    pub(crate) fn clone_unshaped(&self) -> Self {
+        let mut new = Self::new(self.text.clone(), self.attrs_list.clone(), self.shaping);
+        new.wrap = self.wrap;
+        new.align = self.align;
+        new.line_height_opt = self.line_height_opt;
+        new.paragraph_style = self.paragraph_style;
         new
     }
 
@@ -179,24 +296,63 @@ impl BufferLine {
         font_system: &mut FontSystem,
     ) -> &ShapeLine {
         if self.shape_opt.is_none() {
-            self.shape_opt = Some(ShapeLine::new_in_buffer(
+            self.shape_opt = Some(Arc::new(ShapeLine::new_in_buffer_with_direction(
                 scratch,
                 font_system,
                 &self.text,
                 &self.attrs_list,
                 self.shaping,
-            ));
+                self.paragraph_style.direction,
+            )));
             self.layout_opt = None;
         }
         self.shape_opt.as_ref().expect("shape not found")
     }
 
     /// Get line shaping cache
-    pub fn shape_opt(&self) -> &Option<ShapeLine> {
-        &self.shape_opt
+    pub fn shape_opt(&self) -> Option<&ShapeLine> {
+        self.shape_opt.as_deref()
+    }
+
+    /// Hash of everything that affects shaping of this line
+    pub(crate) fn shape_hash(&self) -> u64 {
+        let mut hasher = rustc_hash::FxHasher::default();
+        self.text.hash(&mut hasher);
+        self.attrs_list.defaults().hash(&mut hasher);
+        for (range, attrs) in self.attrs_list.spans() {
+            range.hash(&mut hasher);
+            attrs.hash(&mut hasher);
+        }
+        self.shaping.hash(&mut hasher);
+        self.paragraph_style.direction.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Copy of everything that affects shaping of this line, to compare with other lines
+    pub(crate) fn shape_key(&self) -> ShapeKey {
+        ShapeKey {
+            text: self.text.clone(),
+            attrs_list: self.attrs_list.clone(),
+            shaping: self.shaping,
+            direction: self.paragraph_style.direction,
+        }
+    }
+
+    /// Get line shaping cache, to share it with other lines
+    pub(crate) fn shape_arc_opt(&self) -> Option<&Arc<ShapeLine>> {
+        self.shape_opt.as_ref()
+    }
+
+    /// Set line shaping cache, resetting layout
+    pub(crate) fn set_shape(&mut self, shape: Arc<ShapeLine>) {
+        self.shape_opt = Some(shape);
+        self.layout_opt = None;
     }
 
     /// Layout line, will cache results
+    ///
+    /// Tabs advance to multiples of the default [`Buffer::tab_size`], see
+    /// [`BufferLine::layout_in_buffer_with_tabs`] to use other tab settings.
     pub fn layout(
         &mut self,
         font_system: &mut FontSystem,
@@ -204,17 +360,19 @@ impl BufferLine {
         width: f32,
         wrap: Wrap,
     ) -> &[LayoutLine] {
-        if self.layout_opt.is_none() {
-            self.wrap = wrap;
-            let align = self.align;
-            let shape = self.shape(font_system);
-            let layout = shape.layout(font_size, width, wrap, align);
-            self.layout_opt = Some(layout);
-        }
-        self.layout_opt.as_ref().expect("layout not found")
+        self.layout_in_buffer(
+            &mut ShapeBuffer::default(),
+            font_system,
+            font_size,
+            width,
+            wrap,
+        )
     }
 
     /// Layout a line using a pre-existing shape buffer.
+    ///
+    /// Tabs advance to multiples of the default [`Buffer::tab_size`], see
+    /// [`BufferLine::layout_in_buffer_with_tabs`] to use other tab settings.
     pub fn layout_in_buffer(
         &mut self,
         scratch: &mut ShapeBuffer,
@@ -223,19 +381,262 @@ impl BufferLine {
         width: f32,
         wrap: Wrap,
     ) -> &[LayoutLine] {
+        self.layout_in_buffer_with_tabs(
+            scratch,
+            font_system,
+            font_size,
+            width,
+            wrap,
+            &[],
+            DEFAULT_TAB_SIZE,
+        )
+    }
+
+    /// Layout a line using a pre-existing shape buffer, moving tabs to the provided tab stops or
+    /// else to multiples of `tab_size` spaces, like [`Buffer`] does with the same settings.
+    ///
+    /// See [`Buffer::set_tab_stops`] and [`Buffer::set_tab_size`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn layout_in_buffer_with_tabs(
+        &mut self,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        font_size: f32,
+        width: f32,
+        wrap: Wrap,
+        tab_stops: &[f32],
+        tab_size: u8,
+    ) -> &[LayoutLine] {
+        self.layout_in_buffer_with_options(
+            scratch,
+            font_system,
+            LayoutOptions {
+                font_size,
+                width,
+                wrap,
+                tab_stops,
+                tab_size,
+                balance: false,
+                default_align: None,
+                overflow: Overflow::Clip,
+                ellipsis: "",
+            },
+        )
+    }
+
+    /// Layout a line using a pre-existing shape buffer and the layout settings of a [`Buffer`]
+    ///
+    /// If `options.wrap` is [`Wrap::None`] and `options.overflow` is [`Overflow::Ellipsis`], a
+    /// line wider than `options.width` is truncated to end with `options.ellipsis`.
+    pub(crate) fn layout_in_buffer_with_options(
+        &mut self,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        options: LayoutOptions,
+    ) -> &[LayoutLine] {
+        let LayoutOptions {
+            font_size,
+            width,
+            wrap,
+            tab_stops,
+            tab_size,
+            balance,
+            default_align,
+            overflow,
+            ellipsis,
+        } = options;
         if self.layout_opt.is_none() {
             self.wrap = wrap;
-            let align = self.align;
+            let align = self
+                .paragraph_style
+                .alignment
+                .or(self.align)
+                .or(self.attrs_list.get_span(0).align_opt)
+                .or(default_align);
+            let indent = self.paragraph_style.indent;
+            let tab_width = self.tab_width(scratch, font_system, font_size, tab_size);
             let shape = self.shape_in_buffer(scratch, font_system);
+            let rtl = shape.rtl;
             let mut layout = Vec::with_capacity(1);
-            shape.layout_to_buffer(scratch, font_size, width, wrap, align, &mut layout);
+            let layout_width = (width - indent).max(0.0);
+            shape.layout_to_buffer_with_tabs(
+                scratch,
+                font_size,
+                layout_width,
+                wrap,
+                align,
+                tab_stops,
+                tab_width,
+                &mut layout,
+            );
+            if balance && wrap != Wrap::None && layout.len() > 1 {
+                // Search for the narrowest width that keeps the same number of lines
+                let lines = layout.len();
+                let mut low = layout_width / lines as f32;
+                let mut high = layout_width;
+                let mut test_layout = Vec::with_capacity(lines);
+                for _ in 0..8 {
+                    let mid = (low + high) / 2.0;
+                    test_layout.clear();
+                    shape.layout_to_buffer_with_tabs(
+                        scratch,
+                        font_size,
+                        mid,
+                        wrap,
+                        align,
+                        tab_stops,
+                        tab_width,
+                        &mut test_layout,
+                    );
+                    if test_layout.len() <= lines {
+                        high = mid;
+                    } else {
+                        low = mid;
+                    }
+                }
+
+                if high < layout_width {
+                    layout.clear();
+                    shape.layout_to_buffer_with_tabs(
+                        scratch,
+                        font_size,
+                        high,
+                        wrap,
+                        align,
+                        tab_stops,
+                        tab_width,
+                        &mut layout,
+                    );
+
+                    // Keep the alignment relative to the full width
+                    let offset = match align {
+                        Some(Align::Start) if rtl => layout_width - high,
+                        Some(Align::Start) | Some(Align::Left) => 0.0,
+                        Some(Align::Right) => layout_width - high,
+                        Some(Align::Center) => (layout_width - high) / 2.0,
+                        Some(Align::End) if !rtl => layout_width - high,
+                        Some(Align::End) => 0.0,
+                        Some(Align::Justified) | None if rtl => layout_width - high,
+                        Some(Align::Justified) | None => 0.0,
+                    };
+                    if offset != 0.0 {
+                        for layout_line in layout.iter_mut() {
+                            for glyph in layout_line.glyphs.iter_mut() {
+                                glyph.x += offset;
+                            }
+                        }
+                    }
+                }
+            }
+            if wrap == Wrap::None && overflow == Overflow::Ellipsis {
+                if let Some(layout_line) = layout.first_mut() {
+                    if layout_line.w > layout_width {
+                        // Use the attributes of the text at the width for the ellipsis
+                        let mut x = 0.0;
+                        let index = layout_line
+                            .glyphs
+                            .iter()
+                            .find(|glyph| {
+                                x += glyph.w;
+                                x > layout_width
+                            })
+                            .map_or(0, |glyph| glyph.start);
+                        let ellipsis_shape = ShapeLine::new_in_buffer(
+                            scratch,
+                            font_system,
+                            ellipsis,
+                            &AttrsList::new(self.attrs_list.get_span(index)),
+                            self.shaping,
+                        );
+                        let mut ellipsis_layout = Vec::with_capacity(1);
+                        ellipsis_shape.layout_to_buffer(
+                            scratch,
+                            font_size,
+                            0.0,
+                            Wrap::None,
+                            Some(Align::Start),
+                            &mut ellipsis_layout,
+                        );
+                        let mut ellipsis_line = ellipsis_layout.remove(0);
+                        if ellipsis_shape.rtl {
+                            // Right-to-left text is laid out to the left of zero
+                            for glyph in ellipsis_line.glyphs.iter_mut() {
+                                glyph.x += ellipsis_line.w;
+                            }
+                        }
+                        layout_line.truncate_with_ellipsis(layout_width, rtl, ellipsis_line);
+                    }
+                }
+            }
+            // RTL lines are laid out from the right edge, so they already end before the indent
+            if !rtl && indent != 0.0 {
+                for layout_line in layout.iter_mut() {
+                    for glyph in layout_line.glyphs.iter_mut() {
+                        glyph.x += indent;
+                    }
+                }
+            }
             self.layout_opt = Some(layout);
         }
         self.layout_opt.as_ref().expect("layout not found")
     }
 
+    /// Get the width of `tab_size` spaces with the attributes of the first tab, or zero if there
+    /// are no tabs
+    fn tab_width(
+        &self,
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        font_size: f32,
+        tab_size: u8,
+    ) -> f32 {
+        let index = match self.text.find('\t') {
+            Some(some) if tab_size > 0 => some,
+            _ => return 0.0,
+        };
+        let space_shape = ShapeLine::new_in_buffer(
+            scratch,
+            font_system,
+            " ",
+            &AttrsList::new(self.attrs_list.get_span(index)),
+            self.shaping,
+        );
+        let mut space_layout = Vec::with_capacity(1);
+        space_shape.layout_to_buffer(
+            scratch,
+            font_size,
+            0.0,
+            Wrap::None,
+            Some(Align::Start),
+            &mut space_layout,
+        );
+        space_layout
+            .first()
+            .map_or(0.0, |layout_line| layout_line.w)
+            * f32::from(tab_size)
+    }
+
     /// Get line layout cache
     pub fn layout_opt(&self) -> &Option<Vec<LayoutLine>> {
         &self.layout_opt
     }
 }
+
+/// Everything that affects shaping of a [`BufferLine`], see [`BufferLine::shape_key`]
+#[derive(Debug)]
+pub(crate) struct ShapeKey {
+    text: String,
+    attrs_list: AttrsList,
+    shaping: Shaping,
+    direction: BaseDirection,
+}
+
+impl ShapeKey {
+    /// Returns true if `line` would be shaped the same as the line this key was made from
+    pub(crate) fn matches(&self, line: &BufferLine) -> bool {
+        self.text == line.text
+            && self.attrs_list == line.attrs_list
+            && self.shaping == line.shaping
+            && self.direction == line.paragraph_style.direction
+    }
+}
@@ -1,7 +1,11 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use alloc::sync::Arc;
+
+use crate::FontVariations;
+
 /// Key for building a glyph cache
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct CacheKey {
     /// Font ID
     pub font_id: fontdb::ID,
@@ -13,6 +17,8 @@ pub struct CacheKey {
     pub x_bin: SubpixelBin,
     /// Binning of fractional Y offset
     pub y_bin: SubpixelBin,
+    /// Variable font axes, see [`crate::Attrs::font_variation`]
+    pub font_variations: Option<Arc<FontVariations>>,
 }
 
 impl CacheKey {
@@ -31,6 +37,7 @@ impl CacheKey {
                 font_size_bits: font_size.to_bits(),
                 x_bin,
                 y_bin,
+                font_variations: None,
             },
             x,
             y,
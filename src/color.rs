@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Helpers for manipulating [`Color`]s
+
+use crate::Color;
+
+fn mix_channel(a: u8, b: u8, t: f32) -> u8 {
+    let value = f32::from(a) + (f32::from(b) - f32::from(a)) * t;
+    (value.clamp(0.0, 255.0) + 0.5) as u8
+}
+
+/// Linearly interpolate between `a` and `b`, including alpha
+///
+/// A `t` of 0.0 returns `a` and a `t` of 1.0 returns `b`. `t` is clamped to that range.
+pub fn color_blend(a: Color, b: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    Color::rgba(
+        mix_channel(a.r(), b.r(), t),
+        mix_channel(a.g(), b.g(), t),
+        mix_channel(a.b(), b.b(), t),
+        mix_channel(a.a(), b.a(), t),
+    )
+}
+
+/// Replace the alpha of `c` with `a`, where 0.0 is transparent and 1.0 is opaque
+pub fn color_with_alpha(c: Color, a: f32) -> Color {
+    Color::rgba(c.r(), c.g(), c.b(), mix_channel(0, 0xFF, a.clamp(0.0, 1.0)))
+}
+
+/// Move `c` towards white by `amount`, from 0.0 to 1.0, keeping its alpha
+pub fn color_lighten(c: Color, amount: f32) -> Color {
+    let white = Color::rgba(0xFF, 0xFF, 0xFF, c.a());
+    color_blend(c, white, amount)
+}
+
+/// Move `c` towards black by `amount`, from 0.0 to 1.0, keeping its alpha
+pub fn color_darken(c: Color, amount: f32) -> Color {
+    let black = Color::rgba(0, 0, 0, c.a());
+    color_blend(c, black, amount)
+}
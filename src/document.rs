@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "swash")]
+use core::cmp;
+use core::ops::Range;
+
+#[cfg(feature = "swash")]
+use crate::Color;
+use crate::{BorrowedWithFontSystem, Buffer, Cursor, FontSystem};
+
+/// A document made of multiple [`Buffer`]s (or blocks) stacked vertically
+///
+/// Each block can use its own wrap width, fonts, and alignment, and takes as much vertical space
+/// as its height. Call [`DocumentLayout::update`] after modifying blocks to shape them and compute
+/// their vertical offsets, or [`DocumentLayout::fit_blocks_to_contents`] to also resize them to
+/// their contents.
+#[derive(Debug)]
+pub struct DocumentLayout {
+    /// [`Buffer`]s (or blocks) of the document, from top to bottom
+    pub blocks: Vec<Buffer>,
+    /// Top and height of each block, in pixels
+    bounds: Vec<(f32, f32)>,
+    total_height: f32,
+}
+
+impl DocumentLayout {
+    /// Create a new [`DocumentLayout`] with the provided blocks
+    ///
+    /// Blocks are not shaped until [`DocumentLayout::update`] is called.
+    pub fn new(blocks: Vec<Buffer>) -> Self {
+        Self {
+            blocks,
+            bounds: Vec::new(),
+            total_height: 0.0,
+        }
+    }
+
+    /// Mutably borrows the document together with an [`FontSystem`] for more convenient methods
+    pub fn borrow_with<'a>(
+        &'a mut self,
+        font_system: &'a mut FontSystem,
+    ) -> BorrowedWithFontSystem<'a, DocumentLayout> {
+        BorrowedWithFontSystem {
+            inner: self,
+            font_system,
+        }
+    }
+
+    /// Shape the visible lines of all blocks and compute their vertical offsets from their
+    /// heights
+    ///
+    /// The size and scroll of the blocks are not changed, see
+    /// [`DocumentLayout::fit_blocks_to_contents`] to show all lines of every block.
+    pub fn update(&mut self, font_system: &mut FontSystem) {
+        self.bounds.clear();
+        let mut top = 0.0;
+        for block in self.blocks.iter_mut() {
+            block.shape_until_scroll(font_system);
+
+            let (_, height) = block.size();
+            self.bounds.push((top, height));
+            top += height;
+        }
+        self.total_height = top;
+    }
+
+    /// Scroll every block to the top and resize it to the height of its contents, keeping its
+    /// width, then update the vertical offsets
+    pub fn fit_blocks_to_contents(&mut self, font_system: &mut FontSystem) {
+        for block in self.blocks.iter_mut() {
+            block.set_scroll(0);
+            block.shape_until(font_system, i32::MAX);
+
+            let (_, height) = block.content_size_unclamped();
+            let (width, _) = block.size();
+            block.set_size(font_system, width, height);
+        }
+        self.update(font_system);
+    }
+
+    /// Get the vertical offset of a block, as computed by the last [`DocumentLayout::update`]
+    pub fn block_offset(&self, block_i: usize) -> Option<f32> {
+        self.bounds.get(block_i).map(|(top, _)| *top)
+    }
+
+    /// Get the sum of all block heights, as computed by the last [`DocumentLayout::update`]
+    pub fn total_height(&self) -> f32 {
+        self.total_height
+    }
+
+    /// Get the indices of the blocks that are at least partly inside a viewport starting `top`
+    /// pixels from the top of the document and `height` pixels tall
+    pub fn visible_blocks(&self, top: f32, height: f32) -> Range<usize> {
+        let start = self
+            .bounds
+            .iter()
+            .position(|(block_top, block_height)| block_top + block_height > top)
+            .unwrap_or(self.bounds.len());
+        let end = self.bounds[start..]
+            .iter()
+            .position(|(block_top, _)| *block_top >= top + height)
+            .map_or(self.bounds.len(), |i| start + i);
+        start..end
+    }
+
+    /// Convert x, y position in the document to a block index and a [`Cursor`] in that block
+    /// (hit detection)
+    pub fn hit(&self, x: f32, y: f32) -> Option<(usize, Cursor)> {
+        let block_i = self
+            .bounds
+            .iter()
+            .position(|(top, height)| y < top + height)
+            .unwrap_or_else(|| self.bounds.len().saturating_sub(1));
+        let (top, _) = self.bounds.get(block_i)?;
+        let cursor = self.blocks.get(block_i)?.hit(x, y - top)?;
+        Some((block_i, cursor))
+    }
+
+    /// Draw the blocks inside a viewport starting `top` pixels from the top of the document and
+    /// `height` pixels tall, with coordinates relative to the viewport
+    ///
+    /// Blocks outside of the viewport are skipped, and drawing is clipped to the viewport.
+    #[cfg(feature = "swash")]
+    pub fn draw<F>(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut crate::SwashCache,
+        color: Color,
+        top: f32,
+        height: f32,
+        mut f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        let viewport_height = height as i32;
+        for block_i in self.visible_blocks(top, height) {
+            let block_top = (self.bounds[block_i].0 - top) as i32;
+            self.blocks[block_i].draw(font_system, cache, color, |x, y, w, h, color| {
+                let y_start = cmp::max(block_top + y, 0);
+                let y_end = cmp::min(block_top + y + h as i32, viewport_height);
+                if y_start < y_end {
+                    f(x, y_start, w, (y_end - y_start) as u32, color);
+                }
+            });
+        }
+    }
+}
+
+impl<'a> BorrowedWithFontSystem<'a, DocumentLayout> {
+    /// Shape the visible lines of all blocks and compute their vertical offsets from their
+    /// heights
+    pub fn update(&mut self) {
+        self.inner.update(self.font_system);
+    }
+
+    /// Scroll every block to the top and resize it to the height of its contents, keeping its
+    /// width, then update the vertical offsets
+    pub fn fit_blocks_to_contents(&mut self) {
+        self.inner.fit_blocks_to_contents(self.font_system);
+    }
+
+    /// Draw the blocks inside a viewport starting `top` pixels from the top of the document and
+    /// `height` pixels tall, with coordinates relative to the viewport
+    #[cfg(feature = "swash")]
+    pub fn draw<F>(
+        &mut self,
+        cache: &mut crate::SwashCache,
+        color: Color,
+        top: f32,
+        height: f32,
+        f: F,
+    ) where
+        F: FnMut(i32, i32, u32, u32, Color),
+    {
+        self.inner
+            .draw(self.font_system, cache, color, top, height, f);
+    }
+}
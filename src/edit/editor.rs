@@ -1,40 +1,1160 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 #[cfg(not(feature = "std"))]
-use alloc::string::String;
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{
     cmp::{self, Ordering},
+    fmt,
     iter::once,
+    mem,
+    ops::Range,
 };
 use unicode_segmentation::UnicodeSegmentation;
 
-#[cfg(feature = "swash")]
-use crate::Color;
-use crate::{
-    Action, Affinity, AttrsList, Buffer, BufferLine, Cursor, Edit, FontSystem, LayoutCursor,
-    Shaping,
-};
+use super::cursor_after_replace;
+use crate::buffer::column_bounds;
+#[cfg(feature = "swash")]
+use crate::Color;
+use crate::{
+    Action, Affinity, AttrsList, Buffer, BufferLine, Cursor, Edit, FontSystem, LayoutCursor, Rect,
+    Shaping, TextDecoration,
+};
+
+/// How [`Action::Drag`] behaves when the drag starts inside the selection
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DragMode {
+    /// Dragging always extends the selection
+    AlwaysExtend,
+    /// Dragging from inside the selection starts a drag of the selection, see
+    /// [`Editor::set_on_drag_start`]
+    DragIfInsideSelection,
+}
+
+/// Callback fired with the start and end of the selection when a drag of the selection starts
+pub type DragStartCallback = Box<dyn FnMut(Cursor, Cursor) + Send + Sync>;
+
+/// Callback fired with the [`Buffer`] when the text of an [`Editor`] changes
+pub type ChangeCallback = Box<dyn Fn(&Buffer) + Send + Sync>;
+
+/// Text being dragged out of an [`Editor`], see [`Editor::begin_drag`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DragData {
+    /// Dragged text
+    pub text: String,
+    /// Start and end of the dragged selection
+    pub source_range: (Cursor, Cursor),
+}
+
+/// A change to the text of an [`Editor`], used for [`Action::Undo`] and [`Action::Redo`]
+///
+/// The changed range starts at `start` and covers `old_text` before the change, and `new_text`
+/// after the change.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EditRecord {
+    /// Start of the changed range
+    pub start: Cursor,
+    /// Text in the changed range before the change
+    pub old_text: String,
+    /// Text in the changed range after the change
+    pub new_text: String,
+    /// Cursor before the change
+    pub cursor_before: Cursor,
+    /// Cursor after the change
+    pub cursor_after: Cursor,
+}
+
+/// Get the position after `text` when it is inserted at `start`
+fn cursor_after_text(start: Cursor, text: &str) -> Cursor {
+    match text.rfind('\n') {
+        Some(i) => Cursor::new(
+            start.line + text.matches('\n').count(),
+            text.len() - (i + 1),
+        ),
+        None => Cursor::new(start.line, start.index + text.len()),
+    }
+}
+
+/// Get the length of the indentation to remove from the start of `text` to unindent it by one
+/// level of `indent`
+///
+/// This is `indent` if the text starts with it, or else up to as many spaces as there are
+/// characters in `indent`, or a single tab.
+fn unindent_len(text: &str, indent: &str) -> usize {
+    if !indent.is_empty() && text.starts_with(indent) {
+        return indent.len();
+    }
+    let mut len = 0;
+    for c in text.chars().take(indent.chars().count()) {
+        match c {
+            ' ' => len += 1,
+            '\t' => {
+                if len == 0 {
+                    len += 1;
+                }
+                break;
+            }
+            _ => break,
+        }
+    }
+    len
+}
+
+/// Sort cursors and remove those at the same position
+fn merge_cursors(cursors: &mut Vec<Cursor>) {
+    cursors.sort();
+    cursors.dedup_by(|a, b| (a.line, a.index) == (b.line, b.index));
+}
+
+/// Uncommitted text of an input method in the buffer, see [`Editor::set_ime_preedit`]
+#[derive(Clone, Copy, Debug)]
+struct Preedit {
+    start: Cursor,
+    end: Cursor,
+}
+
+/// Groups of [`EditRecord`]s for undo and redo
+#[derive(Debug, Default)]
+struct EditHistory {
+    max_depth: usize,
+    undo: Vec<Vec<EditRecord>>,
+    redo: Vec<Vec<EditRecord>>,
+    group_open: bool,
+    group_hold: bool,
+}
+
+impl EditHistory {
+    fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            ..Default::default()
+        }
+    }
+
+    /// Add a record to the open group, starting a new group if needed
+    ///
+    /// Inserts that continue the last insert of the open group are merged into it.
+    fn push(&mut self, record: EditRecord) {
+        if self.max_depth == 0 {
+            return;
+        }
+        self.redo.clear();
+
+        if self.group_open {
+            if let Some(last) = self.undo.last_mut().and_then(|group| group.last_mut()) {
+                let last_end = cursor_after_text(last.start, &last.new_text);
+                if last.old_text.is_empty()
+                    && record.old_text.is_empty()
+                    && !record.new_text.contains('\n')
+                    && (last_end.line, last_end.index) == (record.start.line, record.start.index)
+                {
+                    last.new_text.push_str(&record.new_text);
+                    last.cursor_after = record.cursor_after;
+                    return;
+                }
+            }
+            if let Some(group) = self.undo.last_mut() {
+                group.push(record);
+                return;
+            }
+        }
+
+        self.undo.push(Vec::from([record]));
+        self.group_open = true;
+        if self.undo.len() > self.max_depth {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Close the open group, so the next record starts a new group
+    fn flush(&mut self) {
+        if !self.group_hold {
+            self.group_open = false;
+        }
+    }
+}
+
+/// Handles [`Action::Click`] and [`Action::MultiClick`], with the number of consecutive clicks
+/// counted by the caller
+pub trait ClickHandler {
+    /// Handle a click at `x`, `y`, where `count` is 1, 2, or 3 when called by the [`Editor`]
+    fn on_click(&self, editor: &mut Editor, x: i32, y: i32, count: u32);
+}
+
+/// The default [`ClickHandler`]: 1 click moves the cursor, 2 clicks select a word, and 3 clicks
+/// select a line
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultClickHandler;
+
+impl ClickHandler for DefaultClickHandler {
+    fn on_click(&self, editor: &mut Editor, x: i32, y: i32, count: u32) {
+        match count {
+            0 | 1 => editor.click(x, y),
+            2 => editor.select_word_at(x, y),
+            3 => editor.select_line_at(x, y),
+            // Counts above 3 cycle back to a single click, like `Action::MultiClick`
+            _ => self.on_click(editor, x, y, (count - 1) % 3 + 1),
+        }
+    }
+}
+
+/// A wrapper of [`Buffer`] for easy editing
+pub struct Editor {
+    buffer: Buffer,
+    cursor: Cursor,
+    cursor_x_opt: Option<i32>,
+    select_opt: Option<Cursor>,
+    cursor_moved: bool,
+    scroll_pixels: f32,
+    drag_mode: DragMode,
+    click_in_selection: bool,
+    drag_started: bool,
+    on_drag_start: Option<DragStartCallback>,
+    click_handler: Option<Box<dyn ClickHandler + Send + Sync>>,
+    previous_action_opt: Option<Action>,
+    text_changed: bool,
+    on_change: Option<ChangeCallback>,
+    history: EditHistory,
+    dirty_rect_opt: Option<Rect>,
+    dirty_scroll: i32,
+    indent: String,
+    preedit_opt: Option<Preedit>,
+    cursors: Vec<Cursor>,
+    column_selection: bool,
+    column_x_opt: Option<f32>,
+}
+
+impl fmt::Debug for Editor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Editor")
+            .field("buffer", &self.buffer)
+            .field("cursor", &self.cursor)
+            .field("cursor_x_opt", &self.cursor_x_opt)
+            .field("select_opt", &self.select_opt)
+            .field("cursor_moved", &self.cursor_moved)
+            .field("scroll_pixels", &self.scroll_pixels)
+            .field("drag_mode", &self.drag_mode)
+            .field("click_in_selection", &self.click_in_selection)
+            .field("drag_started", &self.drag_started)
+            .field("previous_action_opt", &self.previous_action_opt)
+            .field("text_changed", &self.text_changed)
+            .field("history", &self.history)
+            .field("dirty_rect_opt", &self.dirty_rect_opt)
+            .field("dirty_scroll", &self.dirty_scroll)
+            .field("indent", &self.indent)
+            .field("preedit_opt", &self.preedit_opt)
+            .field("cursors", &self.cursors)
+            .field("column_selection", &self.column_selection)
+            .field("column_x_opt", &self.column_x_opt)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Editor {
+    /// Create a new [`Editor`] with the provided [`Buffer`]
+    ///
+    /// Up to 100 groups of changes are kept for [`Action::Undo`].
+    pub fn new(buffer: Buffer) -> Self {
+        Self::new_with_undo_depth(buffer, 100)
+    }
+
+    /// Create a new [`Editor`] with the provided [`Buffer`], keeping up to `undo_depth` groups
+    /// of changes for [`Action::Undo`]. An `undo_depth` of 0 disables undo and redo.
+    pub fn new_with_undo_depth(buffer: Buffer, undo_depth: usize) -> Self {
+        Self {
+            buffer,
+            cursor: Cursor::default(),
+            cursor_x_opt: None,
+            select_opt: None,
+            cursor_moved: false,
+            scroll_pixels: 0.0,
+            drag_mode: DragMode::AlwaysExtend,
+            click_in_selection: false,
+            drag_started: false,
+            on_drag_start: None,
+            click_handler: None,
+            previous_action_opt: None,
+            text_changed: false,
+            on_change: None,
+            history: EditHistory::new(undo_depth),
+            dirty_rect_opt: None,
+            dirty_scroll: 0,
+            indent: String::from("\t"),
+            preedit_opt: None,
+            cursors: Vec::new(),
+            column_selection: false,
+            column_x_opt: None,
+        }
+    }
+
+    /// Get the string inserted by [`Action::Indent`]
+    pub fn indent(&self) -> &str {
+        &self.indent
+    }
+
+    /// Set the string inserted by [`Action::Indent`], such as a tab or a number of spaces
+    ///
+    /// The default is a tab.
+    pub fn set_indent(&mut self, indent: &str) {
+        self.indent = indent.to_string();
+    }
+
+    /// Get all active cursors, in order. This only contains the main cursor unless more cursors
+    /// were added with [`Editor::add_cursor`]
+    pub fn cursors(&self) -> &[Cursor] {
+        if self.cursors.is_empty() {
+            core::slice::from_ref(&self.cursor)
+        } else {
+            &self.cursors
+        }
+    }
+
+    /// Add a cursor, enabling multi-cursor editing
+    ///
+    /// While more than one cursor is active, each [`Action`] is applied to every cursor
+    /// independently, starting from the last one, and cursors that end up at the same position
+    /// are merged. Selections are cleared in this mode. Scrolling is applied once, while
+    /// clicking, selecting, undo, redo, [`Action::Escape`], and [`Edit::set_cursor`] return to a
+    /// single cursor.
+    pub fn add_cursor(&mut self, cursor: Cursor) {
+        if self.cursors.is_empty() {
+            self.cursors.push(self.cursor);
+        }
+        self.cursors.push(cursor);
+        merge_cursors(&mut self.cursors);
+        self.buffer.set_redraw(true);
+    }
+
+    /// Remove all cursors except the main cursor
+    pub fn clear_cursors(&mut self) {
+        if !self.cursors.is_empty() {
+            self.cursors.clear();
+            self.buffer.set_redraw(true);
+        }
+    }
+
+    /// Get whether column selection is enabled, see [`Action::ToggleColumnSelection`]
+    ///
+    /// A column selection is the rectangle between the selection start and the cursor: the text
+    /// between their x positions on each line from the first to the last line of the selection.
+    /// Copying joins the rows with newlines, deleting removes the text of each row, and inserting
+    /// text inserts it on each row, leaving a cursor after each insertion (see
+    /// [`Editor::add_cursor`]). Wrapped lines use their first visual line.
+    pub fn column_selection(&self) -> bool {
+        self.column_selection
+    }
+
+    /// Enable or disable column selection, see [`Editor::column_selection`]
+    pub fn set_column_selection(&mut self, column_selection: bool) {
+        if column_selection != self.column_selection {
+            self.column_selection = column_selection;
+            self.column_x_opt = None;
+            self.buffer.set_redraw(true);
+        }
+    }
+
+    /// Get the uncommitted text of an input method, see [`Editor::set_ime_preedit`]
+    pub fn ime_preedit(&self) -> Option<String> {
+        let preedit = self.preedit_opt?;
+        Some(self.text_between(preedit.start, preedit.end))
+    }
+
+    /// Set the uncommitted text of an input method, replacing the previous preedit text
+    ///
+    /// The text is inserted underlined at the cursor, replacing the selection when a preedit
+    /// starts. The cursor is moved to the end of `cursor_range`, a byte range in `text`, or to
+    /// the end of the text if None. Preedit text is not recorded for undo and is not included
+    /// in copied text. It is removed by any [`Action`], or by setting an empty preedit text.
+    pub fn set_ime_preedit(&mut self, text: &str, cursor_range: Option<(usize, usize)>) {
+        if self.preedit_opt.is_none() {
+            self.delete_selection();
+        }
+        self.remove_ime_preedit();
+        if text.is_empty() {
+            return;
+        }
+
+        let start = self.cursor;
+        let line = &self.buffer.lines[start.line];
+        let attrs = line.attrs_list().get_span(start.index.saturating_sub(1));
+        let text_decoration = attrs.text_decoration | TextDecoration::UNDERLINE;
+        let attrs_list = AttrsList::new(attrs.text_decoration(text_decoration));
+        self.mark_dirty(start.line, None);
+        let end = self
+            .buffer
+            .replace_range_unrecorded(start, start, text, Some(attrs_list));
+        self.preedit_opt = Some(Preedit { start, end });
+
+        let cursor_text = match cursor_range {
+            Some((_, cursor_end)) => text.get(..cursor_end).unwrap_or(text),
+            None => text,
+        };
+        self.cursor = cursor_after_text(start, cursor_text);
+        self.cursor_x_opt = None;
+        self.cursor_moved = true;
+    }
+
+    /// Replace the preedit text of an input method with its committed `text`, which is recorded
+    /// for undo like other inserted text
+    pub fn commit_ime(&mut self, text: &str) {
+        self.remove_ime_preedit();
+        self.insert_string(text, None);
+    }
+
+    /// Apply `action` to each of the cursors, from last to first
+    ///
+    /// Cursors after the one being edited are kept at the same distance from the end of the
+    /// buffer, so they follow the text they were in. All changes form one undo group.
+    fn multi_cursor_action(&mut self, font_system: &mut FontSystem, action: Action) {
+        let group_insert = matches!(action, Action::Insert(character) if character != '\n');
+        if !group_insert {
+            self.history.flush();
+        }
+        self.history.group_hold = true;
+
+        let mut cursors = mem::take(&mut self.cursors);
+        let main_i = match cursors.iter().position(|cursor| *cursor == self.cursor) {
+            Some(main_i) => main_i,
+            None => {
+                cursors.push(self.cursor);
+                cursors.len() - 1
+            }
+        };
+        let mut order: Vec<usize> = (0..cursors.len()).collect();
+        order.sort_by(|a, b| cursors[*b].cmp(&cursors[*a]));
+
+        let mut done: Vec<usize> = Vec::with_capacity(order.len());
+        for i in order {
+            // Store the finished cursors relative to the end of the buffer
+            let from_end: Vec<(usize, usize)> = done
+                .iter()
+                .map(|done_i| {
+                    let cursor = cursors[*done_i];
+                    let lines = self.buffer.lines.len();
+                    let len = self.buffer.lines[cursor.line].text().len();
+                    (lines - 1 - cursor.line, len.saturating_sub(cursor.index))
+                })
+                .collect();
+
+            self.cursor = cursors[i];
+            self.select_opt = None;
+            self.cursor_x_opt = None;
+            self.action(font_system, action.clone());
+            self.select_opt = None;
+            cursors[i] = self.cursor;
+
+            for (done_i, (lines_from_end, index_from_end)) in done.iter().zip(from_end) {
+                let lines = self.buffer.lines.len();
+                let cursor = &mut cursors[*done_i];
+                cursor.line = (lines - 1).saturating_sub(lines_from_end);
+                let len = self.buffer.lines[cursor.line].text().len();
+                cursor.index = len.saturating_sub(index_from_end);
+            }
+            done.push(i);
+        }
+
+        self.history.group_hold = false;
+        self.cursor = cursors[main_i];
+        merge_cursors(&mut cursors);
+        if cursors.len() > 1 {
+            self.cursors = cursors;
+        }
+        self.buffer.set_redraw(true);
+    }
+
+    /// Remove the preedit text, moving the cursor to where it started
+    fn remove_ime_preedit(&mut self) {
+        if let Some(preedit) = self.preedit_opt.take() {
+            self.mark_dirty(preedit.start.line, None);
+            self.buffer
+                .replace_range_unrecorded(preedit.start, preedit.end, "", None);
+            self.cursor = preedit.start;
+            self.cursor_moved = true;
+        }
+    }
+
+    /// Get the current [`DragMode`]
+    pub fn drag_mode(&self) -> DragMode {
+        self.drag_mode
+    }
+
+    /// Set the current [`DragMode`]
+    pub fn set_drag_mode(&mut self, mode: DragMode) {
+        self.drag_mode = mode;
+        self.click_in_selection = false;
+    }
+
+    /// Set the callback fired when a drag of the selection starts in
+    /// [`DragMode::DragIfInsideSelection`]
+    pub fn set_on_drag_start(&mut self, on_drag_start: Option<DragStartCallback>) {
+        self.on_drag_start = on_drag_start;
+    }
+
+    /// Set the callback fired at the end of [`Edit::action`] and [`Edit::insert_string`] when the
+    /// text changed
+    pub fn set_on_change(&mut self, on_change: Option<ChangeCallback>) {
+        self.on_change = on_change;
+    }
+
+    /// Fire the change callback if the text changed since it was last fired
+    fn notify_change(&mut self) {
+        if self.text_changed {
+            self.text_changed = false;
+            if let Some(on_change) = &self.on_change {
+                on_change(&self.buffer);
+            }
+        }
+    }
+
+    /// End the current group of changes, so the next change is undone separately
+    ///
+    /// Every [`Action`] except for [`Action::Insert`] ends the group. Consecutive inserts are
+    /// grouped, so they are undone together.
+    pub fn flush_undo_group(&mut self) {
+        self.history.flush();
+    }
+
+    /// Replace `old_text` at `start` with `new_text`, without recording history
+    ///
+    /// The text is replaced directly in the buffer, so this is not affected by column selection.
+    fn replace_text(&mut self, start: Cursor, old_text: &str, new_text: &str) {
+        let end = cursor_after_text(start, old_text);
+        self.mark_dirty(start.line, None);
+        self.select_opt = None;
+        self.cursor = self
+            .buffer
+            .replace_range_unrecorded(start, end, new_text, None);
+        self.text_changed = true;
+    }
+
+    /// Replace the text from `start` to `end` with `text` and record the change for undo,
+    /// returning the cursor at the end of the inserted text
+    ///
+    /// The text is replaced directly in the buffer, so this is not affected by column selection.
+    /// The cursor, the selection, and all added cursors are moved to stay on the same text. If
+    /// `move_cursor` is true, the cursor is moved to the end of the inserted text instead and the
+    /// selection is cleared.
+    fn replace_range_recorded(
+        &mut self,
+        start: Cursor,
+        end: Cursor,
+        text: &str,
+        attrs_list: Option<AttrsList>,
+        move_cursor: bool,
+    ) -> Cursor {
+        let (start, end) = if (end.line, end.index) < (start.line, start.index) {
+            (end, start)
+        } else {
+            (start, end)
+        };
+        let old_text = self.text_between(start, end);
+        let cursor_before = self.cursor;
+        self.mark_dirty(start.line, None);
+        let new_end = self
+            .buffer
+            .replace_range_unrecorded(start, end, text, attrs_list);
+
+        let main_i_opt = self
+            .cursors
+            .iter()
+            .position(|cursor| *cursor == self.cursor);
+        for cursor in once(&mut self.cursor)
+            .chain(self.select_opt.as_mut())
+            .chain(self.cursors.iter_mut())
+        {
+            *cursor = cursor_after_replace(*cursor, start, end, new_end);
+        }
+        if move_cursor {
+            self.select_opt = None;
+            self.cursor = new_end;
+            if let Some(main_i) = main_i_opt {
+                self.cursors[main_i] = new_end;
+            }
+        }
+        merge_cursors(&mut self.cursors);
+        if self.cursors.len() == 1 {
+            self.cursors.clear();
+        }
+        if self.cursor != cursor_before {
+            self.cursor_moved = true;
+        }
+
+        self.history.push(EditRecord {
+            start,
+            old_text,
+            new_text: self.text_between(start, new_end),
+            cursor_before,
+            cursor_after: self.cursor,
+        });
+        self.text_changed = true;
+        new_end
+    }
+
+    /// Undo the last group of changes, returning true if there was one
+    fn undo(&mut self) -> bool {
+        self.history.flush();
+        let group = match self.history.undo.pop() {
+            Some(some) => some,
+            None => return false,
+        };
+        for record in group.iter().rev() {
+            self.replace_text(record.start, &record.new_text, &record.old_text);
+        }
+        if let Some(record) = group.first() {
+            self.cursor = record.cursor_before;
+        }
+        self.history.redo.push(group);
+        self.cursor_moved = true;
+        true
+    }
+
+    /// Redo the last undone group of changes, returning true if there was one
+    fn redo(&mut self) -> bool {
+        self.history.flush();
+        let group = match self.history.redo.pop() {
+            Some(some) => some,
+            None => return false,
+        };
+        for record in group.iter() {
+            self.replace_text(record.start, &record.old_text, &record.new_text);
+        }
+        if let Some(record) = group.last() {
+            self.cursor = record.cursor_after;
+        }
+        self.history.undo.push(group);
+        self.cursor_moved = true;
+        true
+    }
+
+    /// Get the region of the [`Buffer`] that needs to be redrawn, or None if no redraw is needed
+    ///
+    /// This is the union of the regions changed by edits and by cursor or selection movement
+    /// since the last call of [`Editor::set_redraw`] with false. An edit marks everything from
+    /// the top of its first changed line to the bottom of the buffer, as the lines after it may
+    /// move. If the region is not known, for example after scrolling, the whole buffer is returned.
+    pub fn dirty_rect(&self) -> Option<Rect> {
+        if !self.buffer.redraw() {
+            return None;
+        }
+
+        let (width, height) = self.buffer.size();
+        match self.dirty_rect_opt {
+            Some(rect)
+                if rect.w > 0.0 && rect.h > 0.0 && self.dirty_scroll == self.buffer.scroll() =>
+            {
+                Some(rect)
+            }
+            _ => Some(Rect::new(0.0, 0.0, width, height)),
+        }
+    }
+
+    /// Set redraw needed flag of the [`Buffer`], clearing the region returned by
+    /// [`Editor::dirty_rect`]
+    ///
+    /// Use this instead of [`Buffer::set_redraw`] when using [`Editor::dirty_rect`].
+    pub fn set_redraw(&mut self, redraw: bool) {
+        self.buffer.set_redraw(redraw);
+        self.dirty_rect_opt = None;
+    }
+
+    /// Start tracking a new dirty region if no redraw is pending
+    fn begin_dirty(&mut self) {
+        if !self.buffer.redraw() {
+            self.dirty_rect_opt = Some(Rect::default());
+            self.dirty_scroll = self.buffer.scroll();
+        }
+    }
+
+    /// Add the visible layout runs from `start_line` to `end_line_opt` to the dirty region, or
+    /// everything after `start_line` if `end_line_opt` is None
+    fn mark_dirty(&mut self, start_line: usize, end_line_opt: Option<usize>) {
+        self.begin_dirty();
+
+        let (width, height) = self.buffer.size();
+        let mut top_opt = None;
+        let mut bottom = 0.0;
+        for run in self.buffer.layout_runs() {
+            if run.line_i < start_line {
+                continue;
+            }
+            if let Some(end_line) = end_line_opt {
+                if run.line_i > end_line {
+                    break;
+                }
+            }
+            top_opt.get_or_insert(run.line_top);
+            bottom = run.line_top + run.line_height;
+        }
+        if end_line_opt.is_none() {
+            bottom = height;
+        }
+
+        if let (Some(dirty), Some(top)) = (&mut self.dirty_rect_opt, top_opt) {
+            let rect = Rect::new(0.0, top, width, bottom - top);
+            *dirty = if dirty.w > 0.0 && dirty.h > 0.0 {
+                let top = dirty.y.min(rect.y);
+                let bottom = (dirty.y + dirty.h).max(rect.y + rect.h);
+                Rect::new(0.0, top, width, bottom - top)
+            } else {
+                rect
+            };
+        }
+        self.buffer.set_redraw(true);
+    }
+
+    /// Call the [`ClickHandler`] for a click, or the [`DefaultClickHandler`] if none is set
+    fn handle_click(&mut self, x: i32, y: i32, count: u32) {
+        let click_handler_opt = self.click_handler.take();
+        match &click_handler_opt {
+            Some(click_handler) => click_handler.on_click(self, x, y, count),
+            None => DefaultClickHandler.on_click(self, x, y, count),
+        }
+        self.click_handler = click_handler_opt;
+    }
+
+    /// Set the [`ClickHandler`] used for [`Action::Click`] and [`Action::MultiClick`], or None to use
+    /// [`DefaultClickHandler`]
+    pub fn set_click_handler(
+        &mut self,
+        click_handler: Option<Box<dyn ClickHandler + Send + Sync>>,
+    ) {
+        self.click_handler = click_handler;
+    }
+
+    /// Move the cursor to the position at `x`, `y`, clearing the selection
+    pub fn click(&mut self, x: i32, y: i32) {
+        let hit_opt = self.buffer.hit(x as f32, y as f32);
+
+        self.click_in_selection = false;
+        self.drag_started = false;
+        if self.drag_mode == DragMode::DragIfInsideSelection {
+            if let (Some(hit), Some((start, end))) = (hit_opt, self.selection_bounds()) {
+                let hit_pos = (hit.line, hit.index);
+                if hit_pos > (start.line, start.index) && hit_pos < (end.line, end.index) {
+                    // Keep the selection, a drag may follow
+                    self.click_in_selection = true;
+                }
+            }
+        }
+
+        if self.click_in_selection {
+            // Selection and cursor are kept
+        } else {
+            self.select_opt = None;
+
+            if let Some(new_cursor) = hit_opt {
+                if new_cursor != self.cursor {
+                    let color = self.cursor.color;
+                    self.cursor = new_cursor;
+                    self.cursor.color = color;
+                    self.buffer.set_redraw(true);
+                }
+            }
+        }
+    }
+
+    /// Select the word at `x`, `y`
+    pub fn select_word_at(&mut self, x: i32, y: i32) {
+        self.click_in_selection = false;
+        self.drag_started = false;
+
+        if let Some(hit) = self.buffer.hit(x as f32, y as f32) {
+            self.select_word(hit.line, hit.index);
+        }
+    }
+
+    /// Select the word containing `index` in `line`, from its start to its end
+    ///
+    /// Words use the same boundaries as [`Action::PreviousWord`] and [`Action::NextWord`]. If
+    /// `index` is not in a word, the whitespace or punctuation around it is selected instead.
+    fn select_word(&mut self, line: usize, index: usize) {
+        let text = self.buffer.lines[line].text();
+        let (start, end) = self
+            .buffer
+            .word_at_cursor(Cursor::new(line, index))
+            .map(|(start, end)| (start.index, end.index))
+            .or_else(|| {
+                text.unicode_word_indices()
+                    .map(|(i, word)| (i, i + word.len()))
+                    .find(|&(_, end)| index == end)
+            })
+            .or_else(|| {
+                text.split_word_bound_indices()
+                    .map(|(i, word)| (i, i + word.len()))
+                    .find(|&(start, end)| index >= start && index < end)
+            })
+            .unwrap_or((index, index));
+
+        self.select_opt = Some(Cursor::new(line, start));
+        self.cursor.line = line;
+        self.cursor.index = end;
+        self.cursor.affinity = Affinity::Before;
+        self.cursor_x_opt = None;
+        self.buffer.set_redraw(true);
+    }
+
+    /// Select the line at `x`, `y`
+    pub fn select_line_at(&mut self, x: i32, y: i32) {
+        self.click_in_selection = false;
+        self.drag_started = false;
+
+        if let Some(hit) = self.buffer.hit(x as f32, y as f32) {
+            self.select_line(hit.line);
+        }
+    }
+
+    /// Select all of `line`, not including the line ending
+    fn select_line(&mut self, line: usize) {
+        self.select_opt = Some(Cursor::new(line, 0));
+        self.cursor.line = line;
+        self.cursor.index = self.buffer.lines[line].text().len();
+        self.cursor.affinity = Affinity::Before;
+        self.cursor_x_opt = None;
+        self.buffer.set_redraw(true);
+    }
+
+    /// Start dragging the selection if `x`, `y` is inside it, returning the dragged text
+    ///
+    /// While the drag is pending, [`Action::Drag`] does not extend the selection.
+    pub fn begin_drag(&mut self, x: i32, y: i32) -> Option<DragData> {
+        let hit = self.buffer.hit(x as f32, y as f32)?;
+        let (start, end) = self.selection_bounds()?;
+        let hit_pos = (hit.line, hit.index);
+        if hit_pos <= (start.line, start.index) || hit_pos >= (end.line, end.index) {
+            return None;
+        }
+
+        let text = self.selected_text()?;
+        self.click_in_selection = true;
+        self.drag_started = true;
+        Some(DragData {
+            text,
+            source_range: (start, end),
+        })
+    }
+
+    /// Drop dragged text at `x`, `y`
+    ///
+    /// If `move_source` is true, the source range is deleted from this editor first. Dropping
+    /// inside the source range does nothing.
+    pub fn finish_drop(&mut self, data: DragData, x: i32, y: i32, move_source: bool) {
+        self.click_in_selection = false;
+        self.drag_started = false;
+
+        let mut target = match self.buffer.hit(x as f32, y as f32) {
+            Some(some) => some,
+            None => return,
+        };
+        let (start, end) = data.source_range;
+        let target_pos = (target.line, target.index);
+        if target_pos >= (start.line, start.index) && target_pos <= (end.line, end.index) {
+            return;
+        }
+
+        if move_source {
+            // Delete the source directly, as deleting it as a column selection would remove a
+            // rectangle instead
+            let cursor_before = self.cursor;
+            let old_text = self.text_between(start, end);
+            self.mark_dirty(start.line, None);
+            self.select_opt = None;
+            self.cursor = self.buffer.replace_range_unrecorded(start, end, "", None);
+            self.history.push(EditRecord {
+                start,
+                old_text,
+                new_text: String::new(),
+                cursor_before,
+                cursor_after: self.cursor,
+            });
+            self.text_changed = true;
+
+            // Adjust the target for the deleted text
+            if target_pos > (end.line, end.index) {
+                if target.line == end.line {
+                    target.line = start.line;
+                    target.index = start.index + (target.index - end.index);
+                } else {
+                    target.line -= end.line - start.line;
+                }
+            }
+        }
+
+        let color = self.cursor.color;
+        self.cursor = target;
+        self.cursor.color = color;
+        self.select_opt = None;
+        self.insert_string(&data.text, None);
+        self.cursor_moved = true;
+        self.buffer.set_redraw(true);
+    }
+
+    /// Get the start and end of the current selection, in order
+    fn selection_bounds(&self) -> Option<(Cursor, Cursor)> {
+        let select = self.select_opt?;
+        Some(match select.line.cmp(&self.cursor.line) {
+            cmp::Ordering::Greater => (self.cursor, select),
+            cmp::Ordering::Less => (select, self.cursor),
+            cmp::Ordering::Equal => {
+                /* select.line == self.cursor.line */
+                if select.index < self.cursor.index {
+                    (select, self.cursor)
+                } else {
+                    /* select.index >= self.cursor.index */
+                    (self.cursor, select)
+                }
+            }
+        })
+    }
+
+    /// Get the x position of a cursor in the layout of its line, or 0 if it is not laid out
+    fn cursor_x(&self, cursor: Cursor) -> f32 {
+        let layout = match self
+            .buffer
+            .lines
+            .get(cursor.line)
+            .and_then(|line| line.layout_opt().as_ref())
+        {
+            Some(some) => some,
+            None => return 0.0,
+        };
+        let glyphs = layout
+            .iter()
+            .flat_map(|layout_line| layout_line.glyphs.iter());
+        // Prefer the glyph starting at the cursor over the glyph ending at it
+        for glyph in glyphs.clone() {
+            if glyph.start == cursor.index {
+                return if glyph.level.is_rtl() {
+                    glyph.x + glyph.w
+                } else {
+                    glyph.x
+                };
+            }
+        }
+        for glyph in glyphs {
+            if glyph.end == cursor.index {
+                return if glyph.level.is_rtl() {
+                    glyph.x
+                } else {
+                    glyph.x + glyph.w
+                };
+            }
+        }
+        0.0
+    }
+
+    /// Get the lines and the left and right x positions of the column selection, or None if there
+    /// is no column selection
+    fn column_selection_bounds(&self) -> Option<(Range<usize>, f32, f32)> {
+        if !self.column_selection {
+            return None;
+        }
+        let select = self.select_opt?;
+        let select_x = self.cursor_x(select);
+        let cursor_x = self
+            .column_x_opt
+            .unwrap_or_else(|| self.cursor_x(self.cursor));
+        let lines =
+            cmp::min(select.line, self.cursor.line)..cmp::max(select.line, self.cursor.line) + 1;
+        Some((lines, select_x.min(cursor_x), select_x.max(cursor_x)))
+    }
+
+    /// Get the selected byte range of each line of the column selection, or None if there is no
+    /// column selection
+    fn column_ranges(&self) -> Option<Vec<(usize, Range<usize>)>> {
+        let (lines, x_start, x_end) = self.column_selection_bounds()?;
+        Some(
+            lines
+                .map(|line_i| {
+                    let range = self.buffer.lines[line_i]
+                        .layout_opt()
+                        .as_ref()
+                        .and_then(|layout| layout.first())
+                        .and_then(|layout_line| column_bounds(&layout_line.glyphs, x_start, x_end))
+                        .map_or(0..0, |(range, _)| range);
+                    (line_i, range)
+                })
+                .collect(),
+        )
+    }
+
+    /// Get the currently selected text, with lines separated by `\n`
+    ///
+    /// Unlike [`Edit::copy_selection`], this never changes any editor state.
+    pub fn selected_text(&self) -> Option<String> {
+        if let Some(rows) = self.column_ranges() {
+            let mut text = String::new();
+            for (i, (line_i, range)) in rows.into_iter().enumerate() {
+                if i > 0 {
+                    text.push('\n');
+                }
+                text.push_str(&self.buffer.lines[line_i].text()[range]);
+            }
+            return Some(text);
+        }
+
+        let (start, end) = self.selection_bounds()?;
+        match self.preedit_opt {
+            Some(preedit) => {
+                // Leave out the part of the preedit text in the selection
+                let clamp = |cursor: Cursor| {
+                    let position = (cursor.line, cursor.index);
+                    if position < (start.line, start.index) {
+                        start
+                    } else if position > (end.line, end.index) {
+                        end
+                    } else {
+                        cursor
+                    }
+                };
+                let mut text = self.text_between(start, clamp(preedit.start));
+                text.push_str(&self.text_between(clamp(preedit.end), end));
+                Some(text)
+            }
+            None => Some(self.text_between(start, end)),
+        }
+    }
+
+    /// Get the lines spanned by the selection, not including the last line if the selection ends
+    /// at its start, or None if the selection does not span multiple lines
+    fn selected_lines(&self) -> Option<Range<usize>> {
+        let (start, end) = self.selection_bounds()?;
+        if start.line == end.line {
+            return None;
+        }
+        if end.index == 0 {
+            Some(start.line..end.line)
+        } else {
+            Some(start.line..end.line + 1)
+        }
+    }
+
+    /// Insert the indent at the start of `lines`, or remove one level of indentation from them if
+    /// `unindent` is true, moving the cursor and selection with the text
+    fn indent_lines(&mut self, lines: Range<usize>, unindent: bool) {
+        let cursor_before = self.cursor;
+        let mut records = Vec::new();
+        self.mark_dirty(lines.start, None);
+        for line_i in lines {
+            let start = Cursor::new(line_i, 0);
+            let text = self.buffer.lines[line_i].text();
+            let (old_text, new_text) = if unindent {
+                let len = unindent_len(text, &self.indent);
+                (text[..len].to_string(), String::new())
+            } else {
+                (String::new(), self.indent.clone())
+            };
+            if old_text.is_empty() && new_text.is_empty() {
+                continue;
+            }
+
+            let end = Cursor::new(line_i, old_text.len());
+            self.buffer
+                .replace_range_unrecorded(start, end, &new_text, None);
+            for cursor in once(&mut self.cursor).chain(self.select_opt.as_mut()) {
+                if cursor.line == line_i {
+                    cursor.index = cursor.index.saturating_sub(old_text.len()) + new_text.len();
+                }
+            }
+            records.push((start, old_text, new_text));
+        }
+
+        for (start, old_text, new_text) in records {
+            self.history.push(EditRecord {
+                start,
+                old_text,
+                new_text,
+                cursor_before,
+                cursor_after: self.cursor,
+            });
+            self.text_changed = true;
+        }
+    }
+
+    /// Check if `line_i` is the first line of a paragraph, where paragraphs are separated by
+    /// empty lines
+    fn is_paragraph_start(&self, line_i: usize) -> bool {
+        !self.buffer.lines[line_i].text().is_empty()
+            && (line_i == 0 || self.buffer.lines[line_i - 1].text().is_empty())
+    }
+
+    /// Move the cursor to the first non-whitespace character of its line, or to the start of the
+    /// line if `toggle` is true and the cursor is already there
+    fn move_to_first_non_whitespace(&mut self, toggle: bool) {
+        let text = self.buffer.lines[self.cursor.line].text();
+        let first_non_whitespace = text.len() - text.trim_start().len();
+        self.cursor.index = if toggle && self.cursor.index == first_non_whitespace {
+            0
+        } else {
+            first_non_whitespace
+        };
+        self.cursor.affinity = Affinity::After;
+        self.cursor_x_opt = None;
+        self.buffer.set_redraw(true);
+    }
+
+    /// Swap line `line_i` with the line after it, keeping their attributes and layout
+    ///
+    /// The cursor and selection must already be moved, `cursor_before` is used for undo.
+    fn swap_lines(&mut self, line_i: usize, cursor_before: Cursor) {
+        let first_len = self.buffer.lines[line_i].text().len();
+        let second = self.buffer.lines[line_i + 1].text();
+        let mut removed = String::from("\n");
+        removed.push_str(second);
+        let mut inserted = second.to_string();
+        inserted.push('\n');
+
+        self.mark_dirty(line_i, Some(line_i + 1));
+        self.buffer.lines.swap(line_i, line_i + 1);
+
+        // Recorded as moving the second line before the first
+        self.history.push(EditRecord {
+            start: Cursor::new(line_i, first_len),
+            old_text: removed,
+            new_text: String::new(),
+            cursor_before,
+            cursor_after: self.cursor,
+        });
+        self.history.push(EditRecord {
+            start: Cursor::new(line_i, 0),
+            old_text: String::new(),
+            new_text: inserted,
+            cursor_before,
+            cursor_after: self.cursor,
+        });
+        self.text_changed = true;
+    }
+
+    /// Get the text from `start` to `end`, with lines separated by `\n`
+    fn text_between(&self, start: Cursor, end: Cursor) -> String {
+        let mut selection = String::new();
+        // Take the selection from the first line
+        {
+            // Add selected part of line to string
+            if start.line == end.line {
+                selection.push_str(&self.buffer.lines[start.line].text()[start.index..end.index]);
+            } else {
+                selection.push_str(&self.buffer.lines[start.line].text()[start.index..]);
+                selection.push('\n');
+            }
+        }
 
-/// A wrapper of [`Buffer`] for easy editing
-#[derive(Debug)]
-pub struct Editor {
-    buffer: Buffer,
-    cursor: Cursor,
-    cursor_x_opt: Option<i32>,
-    select_opt: Option<Cursor>,
-    cursor_moved: bool,
-}
+        // Take the selection from all interior lines (if they exist)
+        for line_i in start.line + 1..end.line {
+            selection.push_str(self.buffer.lines[line_i].text());
+            selection.push('\n');
+        }
 
-impl Editor {
-    /// Create a new [`Editor`] with the provided [`Buffer`]
-    pub fn new(buffer: Buffer) -> Self {
-        Self {
-            buffer,
-            cursor: Cursor::default(),
-            cursor_x_opt: None,
-            select_opt: None,
-            cursor_moved: false,
+        // Take the selection from the last line
+        if end.line > start.line {
+            // Add selected part of line to string
+            selection.push_str(&self.buffer.lines[end.line].text()[..end.index]);
         }
+
+        selection
     }
 
     fn set_layout_cursor(&mut self, font_system: &mut FontSystem, cursor: LayoutCursor) {
@@ -87,6 +1207,7 @@ impl Edit for Editor {
 
     fn set_cursor(&mut self, cursor: Cursor) {
         self.cursor = cursor;
+        self.clear_cursors();
     }
 
     fn select_opt(&self) -> Option<Cursor> {
@@ -110,50 +1231,40 @@ impl Edit for Editor {
     }
 
     fn copy_selection(&self) -> Option<String> {
-        let select = self.select_opt?;
+        self.selected_text()
+    }
 
-        let (start, end) = match select.line.cmp(&self.cursor.line) {
-            cmp::Ordering::Greater => (self.cursor, select),
-            cmp::Ordering::Less => (select, self.cursor),
-            cmp::Ordering::Equal => {
-                /* select.line == self.cursor.line */
-                if select.index < self.cursor.index {
-                    (select, self.cursor)
-                } else {
-                    /* select.index >= self.cursor.index */
-                    (self.cursor, select)
-                }
+    fn delete_selection(&mut self) -> bool {
+        if let Some(rows) = self.column_ranges() {
+            // Delete each row of the column selection
+            let cursor_before = self.cursor;
+            self.select_opt = None;
+            if let Some((_, range)) = rows.iter().find(|(line_i, _)| *line_i == self.cursor.line) {
+                self.cursor.index = range.start;
             }
-        };
-
-        let mut selection = String::new();
-        // Take the selection from the first line
-        {
-            // Add selected part of line to string
-            if start.line == end.line {
-                selection.push_str(&self.buffer.lines[start.line].text()[start.index..end.index]);
-            } else {
-                selection.push_str(&self.buffer.lines[start.line].text()[start.index..]);
-                selection.push('\n');
+            self.mark_dirty(rows[0].0, None);
+            for (line_i, range) in rows.into_iter().rev() {
+                if range.is_empty() {
+                    continue;
+                }
+                let start = Cursor::new(line_i, range.start);
+                let end = Cursor::new(line_i, range.end);
+                let old_text = self.buffer.lines[line_i].text()[range].to_string();
+                self.buffer.replace_range_unrecorded(start, end, "", None);
+                self.history.push(EditRecord {
+                    start,
+                    old_text,
+                    new_text: String::new(),
+                    cursor_before,
+                    cursor_after: self.cursor,
+                });
             }
+            self.text_changed = true;
+            return true;
         }
 
-        // Take the selection from all interior lines (if they exist)
-        for line_i in start.line + 1..end.line {
-            selection.push_str(self.buffer.lines[line_i].text());
-            selection.push('\n');
-        }
-
-        // Take the selection from the last line
-        if end.line > start.line {
-            // Add selected part of line to string
-            selection.push_str(&self.buffer.lines[end.line].text()[..end.index]);
-        }
-
-        Some(selection)
-    }
-
-    fn delete_selection(&mut self) -> bool {
+        let old_text = self.selected_text().unwrap_or_default();
+        let cursor_before = self.cursor;
         let select = match self.select_opt.take() {
             Some(some) => some,
             None => return false,
@@ -173,6 +1284,8 @@ impl Edit for Editor {
             }
         };
 
+        self.mark_dirty(start.line, None);
+
         // Reset cursor to start of selection
         self.cursor = start;
 
@@ -217,16 +1330,52 @@ impl Edit for Editor {
             }
         }
 
+        self.history.push(EditRecord {
+            start,
+            old_text,
+            new_text: String::new(),
+            cursor_before,
+            cursor_after: self.cursor,
+        });
+        self.text_changed = true;
         true
     }
 
     fn insert_string(&mut self, data: &str, attrs_list: Option<AttrsList>) {
+        if let Some(rows) = self.column_ranges() {
+            // Insert on each row of the column selection, from last to first, leaving a cursor
+            // after each insertion
+            self.delete_selection();
+            let newlines = data.matches('\n').count();
+            let mut cursors = Vec::with_capacity(rows.len());
+            for (line_i, range) in rows.into_iter().rev() {
+                self.cursor.line = line_i;
+                self.cursor.index = range.start;
+                self.insert_string(data, attrs_list.clone());
+                cursors.push(self.cursor);
+            }
+            // Rows below are moved down by the lines inserted above them
+            for (i, cursor) in cursors.iter_mut().rev().enumerate() {
+                cursor.line += i * newlines;
+            }
+            self.cursor = cursors[cursors.len() - 1];
+            merge_cursors(&mut cursors);
+            if cursors.len() > 1 {
+                self.cursors = cursors;
+            }
+            return;
+        }
+
         self.delete_selection();
         let mut remaining_split_len = data.len();
         if remaining_split_len == 0 {
+            self.notify_change();
             return;
         }
 
+        self.mark_dirty(self.cursor.line, None);
+
+        let cursor_before = self.cursor;
         let line: &mut BufferLine = &mut self.buffer.lines[self.cursor.line];
         let insert_line = self.cursor.line + 1;
 
@@ -293,10 +1442,73 @@ impl Edit for Editor {
         // Append the text after insertion
         self.cursor.index = self.buffer.lines[self.cursor.line].text().len() - after_len;
         self.cursor_moved = true;
+        self.history.push(EditRecord {
+            start: cursor_before,
+            old_text: String::new(),
+            new_text: self.text_between(cursor_before, self.cursor),
+            cursor_before,
+            cursor_after: self.cursor,
+        });
+        self.text_changed = true;
+        self.notify_change();
+    }
+
+    fn replace_range(
+        &mut self,
+        start: Cursor,
+        end: Cursor,
+        text: &str,
+        attrs_list: Option<AttrsList>,
+    ) {
+        self.replace_range_recorded(start, end, text, attrs_list, true);
+        self.notify_change();
+    }
+
+    fn insert_string_at(
+        &mut self,
+        cursor: Cursor,
+        data: &str,
+        attrs_list: Option<AttrsList>,
+    ) -> Cursor {
+        let end = self.replace_range_recorded(cursor, cursor, data, attrs_list, false);
+        self.notify_change();
+        end
     }
 
     fn action(&mut self, font_system: &mut FontSystem, action: Action) {
+        if !self.cursors.is_empty() {
+            match action {
+                Action::Escape
+                | Action::Click { .. }
+                | Action::MultiClick { .. }
+                | Action::ClickExtend { .. }
+                | Action::Drag { .. }
+                | Action::SelectWord
+                | Action::SelectLine
+                | Action::SelectAll
+                | Action::Undo
+                | Action::Redo => self.clear_cursors(),
+                Action::Scroll { .. }
+                | Action::ScrollPixels { .. }
+                | Action::ToggleColumnSelection => {}
+                _ => {
+                    self.multi_cursor_action(font_system, action);
+                    return;
+                }
+            }
+        }
+
+        self.begin_dirty();
+        self.remove_ime_preedit();
         let old_cursor = self.cursor;
+        let old_select_opt = self.select_opt;
+        // Consecutive inserts are grouped for undo
+        let group_insert = matches!(action, Action::Insert(character) if character != '\n');
+        if !group_insert {
+            self.history.flush();
+        }
+        let previous_action_opt = self.previous_action_opt.replace(action.clone());
+        let is_drag = matches!(action, Action::Drag { .. });
 
         match action {
             Action::Previous => {
@@ -345,7 +1557,6 @@ impl Edit for Editor {
             Action::Left => {
                 let rtl_opt = self.buffer.lines[self.cursor.line]
                     .shape_opt()
-                    .as_ref()
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
@@ -358,7 +1569,6 @@ impl Edit for Editor {
             Action::Right => {
                 let rtl_opt = self.buffer.lines[self.cursor.line]
                     .shape_opt()
-                    .as_ref()
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
@@ -426,22 +1636,117 @@ impl Edit for Editor {
                 self.set_layout_cursor(font_system, cursor);
                 self.cursor_x_opt = None;
             }
+            Action::SmartHome => {
+                self.move_to_first_non_whitespace(true);
+            }
             Action::End => {
                 let mut cursor = self.buffer.layout_cursor(&self.cursor);
                 cursor.glyph = usize::max_value();
                 self.set_layout_cursor(font_system, cursor);
                 self.cursor_x_opt = None;
             }
+            Action::PreviousExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::Previous);
+            }
+            Action::NextExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::Next);
+            }
+            Action::LeftExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::Left);
+            }
+            Action::RightExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::Right);
+            }
+            Action::UpExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::Up);
+            }
+            Action::DownExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::Down);
+            }
+            Action::HomeExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::Home);
+            }
+            Action::EndExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::End);
+            }
             Action::ParagraphStart => {
-                self.cursor.index = 0;
-                self.cursor_x_opt = None;
-                self.buffer.set_redraw(true);
+                // Only toggle to the start of the line if pressed again
+                self.move_to_first_non_whitespace(
+                    previous_action_opt == Some(Action::ParagraphStart),
+                );
             }
             Action::ParagraphEnd => {
-                self.cursor.index = self.buffer.lines[self.cursor.line].text().len();
+                // Move to after the last non-whitespace character, or to the end of the line if
+                // pressed again from there
+                let text = self.buffer.lines[self.cursor.line].text();
+                let last_non_whitespace = text.trim_end().len();
+                self.cursor.index = if previous_action_opt == Some(Action::ParagraphEnd)
+                    && self.cursor.index == last_non_whitespace
+                {
+                    text.len()
+                } else {
+                    last_non_whitespace
+                };
                 self.cursor_x_opt = None;
                 self.buffer.set_redraw(true);
             }
+            Action::PreviousParagraph => {
+                // Skip the start of the current paragraph if the cursor is after it
+                let last_line = if self.cursor.index > 0 {
+                    self.cursor.line + 1
+                } else {
+                    self.cursor.line
+                };
+                self.cursor.line = (0..last_line)
+                    .rev()
+                    .find(|&line_i| self.is_paragraph_start(line_i))
+                    .unwrap_or(0);
+                self.cursor.index = 0;
+                self.cursor.affinity = Affinity::After;
+                self.cursor_x_opt = None;
+            }
+            Action::NextParagraph => {
+                match (self.cursor.line + 1..self.buffer.lines.len())
+                    .find(|&line_i| self.is_paragraph_start(line_i))
+                {
+                    Some(line_i) => {
+                        self.cursor.line = line_i;
+                        self.cursor.index = 0;
+                        self.cursor.affinity = Affinity::After;
+                    }
+                    None => {
+                        // Move to the end of the buffer after the last paragraph
+                        self.cursor.line = self.buffer.lines.len() - 1;
+                        self.cursor.index = self.buffer.lines[self.cursor.line].text().len();
+                        self.cursor.affinity = Affinity::Before;
+                    }
+                }
+                self.cursor_x_opt = None;
+            }
             Action::PageUp => {
                 self.action(font_system, Action::Vertical(-self.buffer.size().1 as i32));
             }
@@ -482,20 +1787,35 @@ impl Edit for Editor {
                     self.insert_string(str_ref, None);
                 }
             }
+            Action::InsertStr(text) => {
+                self.insert_string(&text, None);
+            }
             Action::Enter => {
                 self.delete_selection();
 
+                self.mark_dirty(self.cursor.line, None);
+                let cursor_before = self.cursor;
                 let new_line = self.buffer.lines[self.cursor.line].split_off(self.cursor.index);
 
                 self.cursor.line += 1;
                 self.cursor.index = 0;
 
                 self.buffer.lines.insert(self.cursor.line, new_line);
+                self.history.push(EditRecord {
+                    start: cursor_before,
+                    old_text: String::new(),
+                    new_text: "\n".to_string(),
+                    cursor_before,
+                    cursor_after: self.cursor,
+                });
+                self.text_changed = true;
             }
             Action::Backspace => {
                 if self.delete_selection() {
                     // Deleted selection
                 } else if self.cursor.index > 0 {
+                    self.mark_dirty(self.cursor.line, None);
+                    let cursor_before = self.cursor;
                     let line = &mut self.buffer.lines[self.cursor.line];
 
                     // Get text line after cursor
@@ -514,11 +1834,21 @@ impl Edit for Editor {
                     self.cursor.index = prev_index;
 
                     // Remove character
-                    line.split_off(self.cursor.index);
+                    let removed = line.split_off(self.cursor.index);
 
                     // Add text after cursor
                     line.append(after);
+                    self.history.push(EditRecord {
+                        start: self.cursor,
+                        old_text: removed.into_text(),
+                        new_text: String::new(),
+                        cursor_before,
+                        cursor_after: self.cursor,
+                    });
+                    self.text_changed = true;
                 } else if self.cursor.line > 0 {
+                    self.mark_dirty(self.cursor.line - 1, None);
+                    let cursor_before = self.cursor;
                     let mut line_index = self.cursor.line;
                     let old_line = self.buffer.lines.remove(line_index);
                     line_index -= 1;
@@ -529,12 +1859,21 @@ impl Edit for Editor {
                     self.cursor.index = line.text().len();
 
                     line.append(old_line);
+                    self.history.push(EditRecord {
+                        start: self.cursor,
+                        old_text: "\n".to_string(),
+                        new_text: String::new(),
+                        cursor_before,
+                        cursor_after: self.cursor,
+                    });
+                    self.text_changed = true;
                 }
             }
             Action::Delete => {
                 if self.delete_selection() {
                     // Deleted selection
                 } else if self.cursor.index < self.buffer.lines[self.cursor.line].text().len() {
+                    self.mark_dirty(self.cursor.line, None);
                     let line = &mut self.buffer.lines[self.cursor.line];
 
                     let range_opt = line
@@ -545,35 +1884,149 @@ impl Edit for Editor {
                         .map(|(i, c)| i..(i + c.len()));
 
                     if let Some(range) = range_opt {
+                        let cursor_before = self.cursor;
                         self.cursor.index = range.start;
 
                         // Get text after deleted EGC
                         let after = line.split_off(range.end);
 
                         // Delete EGC
-                        line.split_off(range.start);
+                        let removed = line.split_off(range.start);
 
                         // Add text after deleted EGC
                         line.append(after);
+                        self.history.push(EditRecord {
+                            start: self.cursor,
+                            old_text: removed.into_text(),
+                            new_text: String::new(),
+                            cursor_before,
+                            cursor_after: self.cursor,
+                        });
+                        self.text_changed = true;
                     }
                 } else if self.cursor.line + 1 < self.buffer.lines.len() {
+                    self.mark_dirty(self.cursor.line, None);
                     let old_line = self.buffer.lines.remove(self.cursor.line + 1);
                     self.buffer.lines[self.cursor.line].append(old_line);
+                    self.history.push(EditRecord {
+                        start: Cursor::new(self.cursor.line, self.cursor.index),
+                        old_text: "\n".to_string(),
+                        new_text: String::new(),
+                        cursor_before: self.cursor,
+                        cursor_after: self.cursor,
+                    });
+                    self.text_changed = true;
                 }
             }
-            Action::Click { x, y } => {
+            Action::LineDuplicate => {
+                self.mark_dirty(self.cursor.line, None);
+                let cursor_before = self.cursor;
+                let line = self.buffer.lines[self.cursor.line].clone_unshaped();
+                let start = Cursor::new(self.cursor.line, line.text().len());
+                let mut new_text = String::from("\n");
+                new_text.push_str(line.text());
+                self.buffer.lines.insert(self.cursor.line + 1, line);
                 self.select_opt = None;
-
-                if let Some(new_cursor) = self.buffer.hit(x as f32, y as f32) {
-                    if new_cursor != self.cursor {
-                        let color = self.cursor.color;
-                        self.cursor = new_cursor;
-                        self.cursor.color = color;
-                        self.buffer.set_redraw(true);
-                    }
+                self.cursor.line += 1;
+                self.history.push(EditRecord {
+                    start,
+                    old_text: String::new(),
+                    new_text,
+                    cursor_before,
+                    cursor_after: self.cursor,
+                });
+                self.text_changed = true;
+            }
+            Action::LineDelete => {
+                let cursor_before = self.cursor;
+                let line_i = self.cursor.line;
+                self.select_opt = None;
+                let (start, old_text) = if line_i + 1 < self.buffer.lines.len() {
+                    // Remove the line and its line ending
+                    self.mark_dirty(line_i, None);
+                    let mut old_text = self.buffer.lines.remove(line_i).into_text();
+                    old_text.push('\n');
+                    self.cursor = Cursor::new(line_i, 0);
+                    (self.cursor, old_text)
+                } else if line_i > 0 {
+                    // Remove the last line and the line ending before it
+                    self.mark_dirty(line_i - 1, None);
+                    let mut old_text = String::from("\n");
+                    old_text.push_str(&self.buffer.lines.remove(line_i).into_text());
+                    self.cursor =
+                        Cursor::new(line_i - 1, self.buffer.lines[line_i - 1].text().len());
+                    (self.cursor, old_text)
+                } else {
+                    // Clear the only line
+                    self.mark_dirty(line_i, None);
+                    let old_text = self.buffer.lines[line_i].split_off(0).into_text();
+                    self.cursor = Cursor::new(line_i, 0);
+                    (self.cursor, old_text)
+                };
+                if !old_text.is_empty() {
+                    self.history.push(EditRecord {
+                        start,
+                        old_text,
+                        new_text: String::new(),
+                        cursor_before,
+                        cursor_after: self.cursor,
+                    });
+                    self.text_changed = true;
+                }
+                self.cursor_x_opt = None;
+            }
+            Action::Indent => match self.selected_lines() {
+                Some(lines) => self.indent_lines(lines, false),
+                None => {
+                    let indent = self.indent.clone();
+                    self.insert_string(&indent, None);
                 }
+            },
+            Action::Unindent => {
+                let lines = self
+                    .selected_lines()
+                    .unwrap_or(self.cursor.line..self.cursor.line + 1);
+                self.indent_lines(lines, true);
             }
-            Action::Drag { x, y } => {
+            Action::MoveLineUp => {
+                if self.cursor.line > 0 {
+                    let cursor_before = self.cursor;
+                    self.cursor.line -= 1;
+                    self.select_opt = self
+                        .select_opt
+                        .filter(|select| select.line == cursor_before.line)
+                        .map(|select| Cursor {
+                            line: self.cursor.line,
+                            ..select
+                        });
+                    self.swap_lines(self.cursor.line, cursor_before);
+                }
+            }
+            Action::MoveLineDown => {
+                if self.cursor.line + 1 < self.buffer.lines.len() {
+                    let cursor_before = self.cursor;
+                    self.cursor.line += 1;
+                    self.select_opt = self
+                        .select_opt
+                        .filter(|select| select.line == cursor_before.line)
+                        .map(|select| Cursor {
+                            line: self.cursor.line,
+                            ..select
+                        });
+                    self.swap_lines(cursor_before.line, cursor_before);
+                }
+            }
+            Action::Click { x, y } => {
+                self.handle_click(x, y, 1);
+            }
+            Action::MultiClick { x, y, count } => {
+                // Counts above 3 cycle back to a single click
+                self.handle_click(x, y, (count.max(1) - 1) % 3 + 1);
+            }
+            Action::ClickExtend { x, y } => {
+                self.click_in_selection = false;
+                self.drag_started = false;
+
                 if self.select_opt.is_none() {
                     self.select_opt = Some(self.cursor);
                     self.buffer.set_redraw(true);
@@ -588,11 +2041,55 @@ impl Edit for Editor {
                     }
                 }
             }
+            Action::Drag { x, y } => {
+                if self.click_in_selection {
+                    // Drag the selection instead of extending it
+                    if !self.drag_started {
+                        self.drag_started = true;
+                        if let (Some((start, end)), Some(on_drag_start)) =
+                            (self.selection_bounds(), &mut self.on_drag_start)
+                        {
+                            on_drag_start(start, end);
+                        }
+                    }
+                } else {
+                    if self.select_opt.is_none() {
+                        self.select_opt = Some(self.cursor);
+                        self.buffer.set_redraw(true);
+                    }
+
+                    if let Some(new_cursor) = self.buffer.hit(x as f32, y as f32) {
+                        if new_cursor != self.cursor {
+                            let color = self.cursor.color;
+                            self.cursor = new_cursor;
+                            self.cursor.color = color;
+                            self.buffer.set_redraw(true);
+                        }
+                    }
+
+                    if self.column_selection {
+                        // Column selections follow the pointer past the end of lines
+                        self.column_x_opt = Some(x as f32);
+                        self.buffer.set_redraw(true);
+                    }
+                }
+            }
             Action::Scroll { lines } => {
                 let mut scroll = self.buffer.scroll();
                 scroll += lines;
                 self.buffer.set_scroll(scroll);
             }
+            Action::ScrollPixels { pixels } => {
+                // Scroll units are layout lines, which include the line spacing
+                let line_height = self.buffer.metrics().line_height * self.buffer.line_spacing();
+                self.scroll_pixels += pixels;
+                let lines = (self.scroll_pixels / line_height) as i32;
+                if lines != 0 {
+                    // Carry over the remainder to the next scroll
+                    self.scroll_pixels -= lines as f32 * line_height;
+                    self.action(font_system, Action::Scroll { lines });
+                }
+            }
             Action::PreviousWord => {
                 let line: &mut BufferLine = &mut self.buffer.lines[self.cursor.line];
                 if self.cursor.index > 0 {
@@ -633,7 +2130,6 @@ impl Edit for Editor {
             Action::LeftWord => {
                 let rtl_opt = self.buffer.lines[self.cursor.line]
                     .shape_opt()
-                    .as_ref()
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
@@ -646,7 +2142,6 @@ impl Edit for Editor {
             Action::RightWord => {
                 let rtl_opt = self.buffer.lines[self.cursor.line]
                     .shape_opt()
-                    .as_ref()
                     .map(|shape| shape.rtl);
                 if let Some(rtl) = rtl_opt {
                     if rtl {
@@ -666,10 +2161,80 @@ impl Edit for Editor {
                 self.cursor.index = self.buffer.lines[self.cursor.line].text().len();
                 self.cursor_x_opt = None;
             }
+            Action::PreviousWordExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::PreviousWord);
+            }
+            Action::NextWordExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::NextWord);
+            }
+            Action::BufferStartExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::BufferStart);
+            }
+            Action::BufferEndExtend => {
+                if self.select_opt.is_none() {
+                    self.select_opt = Some(self.cursor);
+                }
+                self.action(font_system, Action::BufferEnd);
+            }
+            Action::SelectWord => {
+                self.select_word(self.cursor.line, self.cursor.index);
+            }
+            Action::SelectLine => {
+                self.select_line(self.cursor.line);
+            }
+            Action::Undo => {
+                if self.undo() {
+                    self.buffer.set_redraw(true);
+                }
+            }
+            Action::Redo => {
+                if self.redo() {
+                    self.buffer.set_redraw(true);
+                }
+            }
+            Action::ToggleColumnSelection => {
+                self.set_column_selection(!self.column_selection);
+            }
+            Action::SelectAll => {
+                let last_line = self.buffer.lines.len().saturating_sub(1);
+                self.select_opt = Some(Cursor::new(0, 0));
+                self.cursor.line = last_line;
+                self.cursor.index = self
+                    .buffer
+                    .lines
+                    .get(last_line)
+                    .map_or(0, |line| line.text().len());
+                self.cursor.affinity = Affinity::Before;
+                self.cursor_x_opt = None;
+                self.buffer.set_redraw(true);
+            }
+        }
+
+        if old_cursor != self.cursor || old_select_opt != self.select_opt {
+            let lines = [old_cursor, self.cursor]
+                .into_iter()
+                .chain(old_select_opt)
+                .chain(self.select_opt)
+                .map(|cursor| cursor.line);
+            let start_line = lines.clone().min().unwrap_or(self.cursor.line);
+            let end_line = lines.max().unwrap_or(self.cursor.line);
+            self.mark_dirty(start_line, Some(end_line));
         }
 
         if old_cursor != self.cursor {
             self.cursor_moved = true;
+            if !is_drag {
+                self.column_x_opt = None;
+            }
 
             /*TODO
             if let Some(glyph) = run.glyphs.get(new_cursor_glyph) {
@@ -687,6 +2252,8 @@ impl Edit for Editor {
             }
             */
         }
+
+        self.notify_change();
     }
 
     /// Draw the editor
@@ -700,11 +2267,10 @@ impl Edit for Editor {
     ) where
         F: FnMut(i32, i32, u32, u32, Color),
     {
-        let line_height = self.buffer.metrics().line_height;
-
         for run in self.buffer.layout_runs() {
             let line_i = run.line_i;
             let line_y = run.line_y;
+            let line_height = run.line_height;
             let line_top = run.line_top;
 
             let cursor_glyph_opt = |cursor: &Cursor| -> Option<(usize, f32)> {
@@ -743,8 +2309,22 @@ impl Edit for Editor {
                 None
             };
 
+            crate::buffer::draw_backgrounds(&run, &mut f);
+
             // Highlight selection (TODO: HIGHLIGHT COLOR!)
-            if let Some(select) = self.select_opt {
+            if let Some((lines, x_start, x_end)) = self.column_selection_bounds() {
+                if lines.contains(&line_i) && !run.is_continuation {
+                    if let Some((x, w)) = run.highlight_columns(x_start, x_end) {
+                        f(
+                            x as i32,
+                            line_top as i32,
+                            w as u32,
+                            line_height as u32,
+                            Color::rgba(color.r(), color.g(), color.b(), 0x33),
+                        );
+                    }
+                }
+            } else if let Some(select) = self.select_opt {
                 let (start, end) = match select.line.cmp(&self.cursor.line) {
                     cmp::Ordering::Greater => (self.cursor, select),
                     cmp::Ordering::Less => (select, self.cursor),
@@ -818,40 +2398,42 @@ impl Edit for Editor {
                 }
             }
 
-            // Draw cursor
-            if let Some((cursor_glyph, cursor_glyph_offset)) = cursor_glyph_opt(&self.cursor) {
-                let x = match run.glyphs.get(cursor_glyph) {
-                    Some(glyph) => {
-                        // Start of detected glyph
-                        if glyph.level.is_rtl() {
-                            (glyph.x + glyph.w - cursor_glyph_offset) as i32
-                        } else {
-                            (glyph.x + cursor_glyph_offset) as i32
-                        }
-                    }
-                    None => match run.glyphs.last() {
+            // Draw cursors
+            for cursor in self.cursors() {
+                if let Some((cursor_glyph, cursor_glyph_offset)) = cursor_glyph_opt(cursor) {
+                    let x = match run.glyphs.get(cursor_glyph) {
                         Some(glyph) => {
-                            // End of last glyph
+                            // Start of detected glyph
                             if glyph.level.is_rtl() {
-                                glyph.x as i32
+                                (glyph.x + glyph.w - cursor_glyph_offset) as i32
                             } else {
-                                (glyph.x + glyph.w) as i32
+                                (glyph.x + cursor_glyph_offset) as i32
                             }
                         }
-                        None => {
-                            // Start of empty line
-                            0
-                        }
-                    },
-                };
-
-                f(
-                    x,
-                    line_top as i32,
-                    1,
-                    line_height as u32,
-                    self.cursor.color.unwrap_or(color),
-                );
+                        None => match run.glyphs.last() {
+                            Some(glyph) => {
+                                // End of last glyph
+                                if glyph.level.is_rtl() {
+                                    glyph.x as i32
+                                } else {
+                                    (glyph.x + glyph.w) as i32
+                                }
+                            }
+                            None => {
+                                // Start of empty line
+                                0
+                            }
+                        },
+                    };
+
+                    f(
+                        x,
+                        line_top as i32,
+                        1,
+                        line_height as u32,
+                        cursor.color.unwrap_or(color),
+                    );
+                }
             }
 
             for glyph in run.glyphs.iter() {
@@ -877,6 +2459,9 @@ impl Edit for Editor {
                     },
                 );
             }
+
+            // Draw decorations, including the underline of IME preedit text
+            crate::buffer::draw_decorations(font_system, &run, color, &mut f);
         }
     }
 }
@@ -1,9 +1,11 @@
 #[cfg(not(feature = "std"))]
 use alloc::string::String;
+use core::{iter::Enumerate, slice};
+use unicode_segmentation::UnicodeSegmentation;
 
 #[cfg(feature = "swash")]
 use crate::Color;
-use crate::{AttrsList, BorrowedWithFontSystem, Buffer, Cursor, FontSystem};
+use crate::{AttrsList, BorrowedWithFontSystem, Buffer, BufferLine, Cursor, FontSystem};
 
 pub use self::editor::*;
 mod editor;
@@ -18,29 +20,82 @@ pub use self::vi::*;
 #[cfg(feature = "vi")]
 mod vi;
 
+/// Move `cursor` to keep it on the same text after the text from `start` to `end` was replaced
+/// by text ending at `new_end`
+///
+/// Cursors inside the replaced text move to `new_end`. If nothing was replaced, a cursor at
+/// `start` moves to `new_end` as well.
+fn cursor_after_replace(cursor: Cursor, start: Cursor, end: Cursor, new_end: Cursor) -> Cursor {
+    let position = (cursor.line, cursor.index);
+    let start_position = (start.line, start.index);
+    let end_position = (end.line, end.index);
+    if position < start_position || (position == start_position && start_position != end_position) {
+        cursor
+    } else if position < end_position {
+        Cursor::new_with_affinity(new_end.line, new_end.index, cursor.affinity)
+    } else if cursor.line == end.line {
+        Cursor::new_with_affinity(
+            new_end.line,
+            new_end.index + (cursor.index - end.index),
+            cursor.affinity,
+        )
+    } else {
+        Cursor::new_with_affinity(
+            cursor.line - end.line + new_end.line,
+            cursor.index,
+            cursor.affinity,
+        )
+    }
+}
+
 /// An action to perform on an [`Editor`]
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Action {
     /// Move cursor to previous character ([Self::Left] in LTR, [Self::Right] in RTL)
     Previous,
     /// Move cursor to next character ([Self::Right] in LTR, [Self::Left] in RTL)
     Next,
+    /// Move cursor to previous character, extending the selection
+    PreviousExtend,
+    /// Move cursor to next character, extending the selection
+    NextExtend,
     /// Move cursor left
     Left,
     /// Move cursor right
     Right,
+    /// Move cursor left, extending the selection
+    LeftExtend,
+    /// Move cursor right, extending the selection
+    RightExtend,
     /// Move cursor up
     Up,
     /// Move cursor down
     Down,
+    /// Move cursor up, extending the selection
+    UpExtend,
+    /// Move cursor down, extending the selection
+    DownExtend,
     /// Move cursor to start of line
     Home,
     /// Move cursor to end of line
     End,
+    /// Move cursor to start of line, extending the selection
+    HomeExtend,
+    /// Move cursor to the first non-whitespace character of the line, or to the start of the
+    /// line if it is already there
+    SmartHome,
+    /// Move cursor to end of line, extending the selection
+    EndExtend,
     /// Move cursor to start of paragraph
     ParagraphStart,
     /// Move cursor to end of paragraph
     ParagraphEnd,
+    /// Move cursor to the start of the previous paragraph, where paragraphs are separated by
+    /// empty lines
+    PreviousParagraph,
+    /// Move cursor to the start of the next paragraph, where paragraphs are separated by empty
+    /// lines
+    NextParagraph,
     /// Move cursor up one page
     PageUp,
     /// Move cursor down one page
@@ -51,22 +106,50 @@ pub enum Action {
     Escape,
     /// Insert character at cursor
     Insert(char),
+    /// Insert string at cursor, replacing the selection
+    InsertStr(String),
     /// Create new line
     Enter,
     /// Delete text behind cursor
     Backspace,
     /// Delete text in front of cursor
     Delete,
+    /// Insert a copy of the line at the cursor below it, moving the cursor to the copy
+    LineDuplicate,
+    /// Delete the line at the cursor, moving the cursor to the start of the next line, or the end
+    /// of the previous line if it was the last line
+    LineDelete,
+    /// Swap the line at the cursor with the line above it, moving the cursor with the line
+    MoveLineUp,
+    /// Swap the line at the cursor with the line below it, moving the cursor with the line
+    MoveLineDown,
+    /// Insert the indent of the editor at the start of each selected line if the selection spans
+    /// multiple lines, or at the cursor otherwise
+    Indent,
+    /// Remove one level of indentation from the start of each selected line, or of the line at
+    /// the cursor if the selection does not span multiple lines
+    Unindent,
     /// Mouse click at specified position
     Click { x: i32, y: i32 },
+    /// Mouse click at specified position, where `count` is the number of consecutive clicks as
+    /// counted by the caller, for example using the double click interval of the platform
+    MultiClick { x: i32, y: i32, count: u32 },
+    /// Mouse click at specified position, extending the selection
+    ClickExtend { x: i32, y: i32 },
     /// Mouse drag to specified position
     Drag { x: i32, y: i32 },
     /// Scroll specified number of lines
     Scroll { lines: i32 },
+    /// Scroll specified number of pixels, carrying over any remainder smaller than a line
+    ScrollPixels { pixels: f32 },
     /// Move cursor to previous word boundary
     PreviousWord,
     /// Move cursor to next word boundary
     NextWord,
+    /// Move cursor to previous word boundary, extending the selection
+    PreviousWordExtend,
+    /// Move cursor to next word boundary, extending the selection
+    NextWordExtend,
     /// Move cursor to next word boundary to the left
     LeftWord,
     /// Move cursor to next word boundary to the right
@@ -75,6 +158,23 @@ pub enum Action {
     BufferStart,
     /// Move cursor to the end of the document
     BufferEnd,
+    /// Move cursor to the start of the document, extending the selection
+    BufferStartExtend,
+    /// Move cursor to the end of the document, extending the selection
+    BufferEndExtend,
+    /// Select the word at the cursor
+    SelectWord,
+    /// Select the line at the cursor, not including the line ending
+    SelectLine,
+    /// Select the whole document
+    SelectAll,
+    /// Undo the last group of changes
+    Undo,
+    /// Redo the last undone group of changes
+    Redo,
+    /// Switch between normal and column (rectangular) selection, see
+    /// [`Editor::column_selection`]
+    ToggleColumnSelection,
 }
 
 /// A trait to allow easy replacements of [`Editor`], like `SyntaxEditor`
@@ -99,12 +199,29 @@ pub trait Edit {
     /// Get the internal [`Buffer`], mutably
     fn buffer_mut(&mut self) -> &mut Buffer;
 
+    /// Get an iterator over the index and [`BufferLine`] of every line
+    fn iter_lines(&self) -> Enumerate<slice::Iter<'_, BufferLine>> {
+        self.buffer().iter_lines()
+    }
+
+    /// Get a mutable iterator over the index and [`BufferLine`] of every line
+    fn iter_lines_mut(&mut self) -> Enumerate<slice::IterMut<'_, BufferLine>> {
+        self.buffer_mut().iter_lines_mut()
+    }
+
     /// Get the current cursor
     fn cursor(&self) -> Cursor;
 
     /// Set the current cursor
     fn set_cursor(&mut self, cursor: Cursor);
 
+    /// Get the column of the current cursor, counted in grapheme clusters
+    fn cursor_column_grapheme(&self) -> usize {
+        let cursor = self.cursor();
+        let text = self.buffer().lines[cursor.line].text();
+        text[..cursor.index].grapheme_indices(true).count()
+    }
+
     /// Get the current selection position
     fn select_opt(&self) -> Option<Cursor>;
 
@@ -114,7 +231,11 @@ pub trait Edit {
     /// Shape lines until scroll, after adjusting scroll if the cursor moved
     fn shape_as_needed(&mut self, font_system: &mut FontSystem);
 
-    /// Copy selection
+    /// Copy selection, with lines separated by `\n`
+    ///
+    /// The text is in logical order, as stored in the buffer, even where right-to-left or mixed
+    /// direction text is displayed in a different visual order. Inserting the returned text with
+    /// [`Edit::insert_string`] reproduces the selected text.
     fn copy_selection(&self) -> Option<String>;
 
     /// Delete selection, adjusting cursor and returning true if there was a selection
@@ -125,6 +246,45 @@ pub trait Edit {
     /// attributes, or with the previous character's attributes if None is given.
     fn insert_string(&mut self, data: &str, attrs_list: Option<AttrsList>);
 
+    /// Replace the text from `start` to `end` with `text`, with the given attributes, or with the
+    /// previous character's attributes if None is given. The cursor is moved to the end of the
+    /// inserted text.
+    fn replace_range(
+        &mut self,
+        start: Cursor,
+        end: Cursor,
+        text: &str,
+        attrs_list: Option<AttrsList>,
+    ) {
+        self.set_select_opt(Some(start));
+        self.set_cursor(end);
+        self.insert_string(text, attrs_list);
+    }
+
+    /// Insert a string at `cursor` with the given attributes, or with the previous character's
+    /// attributes if None is given, returning the cursor at the end of the inserted text.
+    ///
+    /// The current cursor and selection, and any other cursors of an [`Editor`], stay on the same
+    /// text, moving if they are at or after `cursor`.
+    fn insert_string_at(
+        &mut self,
+        cursor: Cursor,
+        data: &str,
+        attrs_list: Option<AttrsList>,
+    ) -> Cursor {
+        let old_cursor = self.cursor();
+        let old_select_opt = self.select_opt();
+        self.set_select_opt(None);
+        self.set_cursor(cursor);
+        self.insert_string(data, attrs_list);
+        let end = self.cursor();
+        self.set_cursor(cursor_after_replace(old_cursor, cursor, cursor, end));
+        self.set_select_opt(
+            old_select_opt.map(|select| cursor_after_replace(select, cursor, cursor, end)),
+        );
+        end
+    }
+
     /// Perform an [Action] on the editor
     fn action(&mut self, font_system: &mut FontSystem, action: Action);
 
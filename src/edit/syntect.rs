@@ -192,11 +192,12 @@ impl<'a> Edit for SyntaxEditor<'a> {
             );
 
             let attrs = line.attrs_list().defaults();
-            let mut attrs_list = AttrsList::new(attrs);
+            let mut attrs_list = AttrsList::new(attrs.clone());
             for (style, _, range) in ranges {
                 attrs_list.add_span(
                     range,
                     attrs
+                        .clone()
                         .color(Color::rgba(
                             style.foreground.r,
                             style.foreground.g,
@@ -262,6 +263,25 @@ impl<'a> Edit for SyntaxEditor<'a> {
         self.editor.insert_string(data, attrs_list);
     }
 
+    fn replace_range(
+        &mut self,
+        start: Cursor,
+        end: Cursor,
+        text: &str,
+        attrs_list: Option<AttrsList>,
+    ) {
+        self.editor.replace_range(start, end, text, attrs_list);
+    }
+
+    fn insert_string_at(
+        &mut self,
+        cursor: Cursor,
+        data: &str,
+        attrs_list: Option<AttrsList>,
+    ) -> Cursor {
+        self.editor.insert_string_at(cursor, data, attrs_list)
+    }
+
     fn action(&mut self, font_system: &mut FontSystem, action: Action) {
         self.editor.action(font_system, action);
     }
@@ -93,6 +93,25 @@ impl<'a> Edit for ViEditor<'a> {
         self.editor.insert_string(data, attrs_list);
     }
 
+    fn replace_range(
+        &mut self,
+        start: Cursor,
+        end: Cursor,
+        text: &str,
+        attrs_list: Option<AttrsList>,
+    ) {
+        self.editor.replace_range(start, end, text, attrs_list);
+    }
+
+    fn insert_string_at(
+        &mut self,
+        cursor: Cursor,
+        data: &str,
+        attrs_list: Option<AttrsList>,
+    ) -> Cursor {
+        self.editor.insert_string_at(cursor, data, attrs_list)
+    }
+
     fn action(&mut self, font_system: &mut FontSystem, action: Action) {
         let old_mode = self.mode;
 
@@ -243,11 +262,10 @@ impl<'a> Edit for ViEditor<'a> {
         F: FnMut(i32, i32, u32, u32, Color),
     {
         let font_size = self.buffer().metrics().font_size;
-        let line_height = self.buffer().metrics().line_height;
-
         for run in self.buffer().layout_runs() {
             let line_i = run.line_i;
             let line_y = run.line_y;
+            let line_height = run.line_height;
 
             let cursor_glyph_opt = |cursor: &Cursor| -> Option<(usize, f32, f32)> {
                 //TODO: better calculation of width
@@ -26,10 +26,17 @@ mod platform;
 #[path = "windows.rs"]
 mod platform;
 
-#[cfg(not(feature = "warn_on_missing_glyphs"))]
+#[cfg(all(not(feature = "tracing"), not(feature = "warn_on_missing_glyphs")))]
 use log::debug as missing_warn;
-#[cfg(feature = "warn_on_missing_glyphs")]
+#[cfg(all(not(feature = "tracing"), feature = "warn_on_missing_glyphs"))]
 use log::warn as missing_warn;
+#[cfg(feature = "tracing")]
+use tracing::warn as missing_warn;
+
+#[cfg(not(feature = "tracing"))]
+use log::debug as fallback_debug;
+#[cfg(feature = "tracing")]
+use tracing::debug as fallback_debug;
 
 pub struct FontFallbackIter<'a> {
     font_system: &'a mut FontSystem,
@@ -37,7 +44,11 @@ pub struct FontFallbackIter<'a> {
     default_families: &'a [&'a Family<'a>],
     default_i: usize,
     scripts: &'a [Script],
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    word: &'a str,
     script_i: (usize, usize),
+    emoji: bool,
+    emoji_i: usize,
     common_i: usize,
     other_i: usize,
     end: bool,
@@ -49,6 +60,7 @@ impl<'a> FontFallbackIter<'a> {
         font_ids: &'a [fontdb::ID],
         default_families: &'a [&'a Family<'a>],
         scripts: &'a [Script],
+        word: &'a str,
     ) -> Self {
         Self {
             font_system,
@@ -56,7 +68,10 @@ impl<'a> FontFallbackIter<'a> {
             default_families,
             default_i: 0,
             scripts,
+            word,
             script_i: (0, 0),
+            emoji: is_emoji_word(word),
+            emoji_i: 0,
             common_i: 0,
             other_i: 0,
             end: false,
@@ -115,8 +130,25 @@ impl<'a> FontFallbackIter<'a> {
 impl<'a> Iterator for FontFallbackIter<'a> {
     type Item = Arc<Font>;
     fn next(&mut self) -> Option<Self::Item> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "font_fallback",
+            word = self.word,
+            scripts = ?self.scripts,
+            locale = self.font_system.locale(),
+        )
+        .entered();
+
         while self.default_i < self.default_families.len() {
             self.default_i += 1;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                family = self
+                    .font_system
+                    .db()
+                    .family_name(self.default_families[self.default_i - 1]),
+                "trying family"
+            );
             let mut monospace_fallback = None;
             for id in self.font_ids.iter() {
                 let default_family = self
@@ -151,6 +183,8 @@ impl<'a> Iterator for FontFallbackIter<'a> {
             while self.script_i.1 < script_families.len() {
                 let script_family = script_families[self.script_i.1];
                 self.script_i.1 += 1;
+                #[cfg(feature = "tracing")]
+                tracing::debug!(family = script_family, "trying family");
                 for id in self.font_ids.iter() {
                     if self.face_contains_family(*id, script_family) {
                         if let Some(font) = self.font_system.get_font(*id) {
@@ -158,7 +192,7 @@ impl<'a> Iterator for FontFallbackIter<'a> {
                         }
                     }
                 }
-                log::debug!(
+                fallback_debug!(
                     "failed to find family '{}' for script {:?} and locale '{}'",
                     script_family,
                     script,
@@ -170,10 +204,36 @@ impl<'a> Iterator for FontFallbackIter<'a> {
             self.script_i.1 = 0;
         }
 
+        // Emoji are in the common script, so try emoji fonts before the common fallbacks, which
+        // may contain a placeholder glyph for them
+        if self.emoji {
+            let emoji_families = emoji_fallback();
+            while self.emoji_i < emoji_families.len() {
+                let emoji_family = emoji_families[self.emoji_i];
+                self.emoji_i += 1;
+                #[cfg(feature = "tracing")]
+                tracing::debug!(family = emoji_family, "trying family");
+                for id in self.font_ids.iter() {
+                    if self.face_contains_family(*id, emoji_family) {
+                        if let Some(font) = self.font_system.get_font(*id) {
+                            return Some(font);
+                        }
+                    }
+                }
+                fallback_debug!("failed to find emoji family '{}'", emoji_family);
+            }
+        }
+
         let common_families = common_fallback();
         while self.common_i < common_families.len() {
             let common_family = common_families[self.common_i];
             self.common_i += 1;
+            if self.emoji && emoji_fallback().contains(&common_family) {
+                // Already tried
+                continue;
+            }
+            #[cfg(feature = "tracing")]
+            tracing::debug!(family = common_family, "trying family");
             for id in self.font_ids.iter() {
                 if self.face_contains_family(*id, common_family) {
                     if let Some(font) = self.font_system.get_font(*id) {
@@ -181,7 +241,7 @@ impl<'a> Iterator for FontFallbackIter<'a> {
                     }
                 }
             }
-            log::debug!("failed to find family '{}'", common_family);
+            fallback_debug!("failed to find family '{}'", common_family);
         }
 
         //TODO: do we need to do this?
@@ -204,3 +264,194 @@ impl<'a> Iterator for FontFallbackIter<'a> {
         None
     }
 }
+
+/// Check if a word contains a character shown as emoji by default, or a pictographic or
+/// keycap character followed by U+FE0F VARIATION SELECTOR-16
+fn is_emoji_word(word: &str) -> bool {
+    let mut chars = word.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_emoji_presentation(c) {
+            return true;
+        }
+        if chars.peek() == Some(&'\u{FE0F}')
+            && (is_extended_pictographic(c) || matches!(c, '0'..='9' | '#' | '*'))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Check if a character has the Unicode 15.0 `Emoji_Presentation` property
+fn is_emoji_presentation(c: char) -> bool {
+    matches!(
+        c,
+        '\u{231A}'..='\u{231B}'
+            | '\u{23E9}'..='\u{23EC}'
+            | '\u{23F0}'
+            | '\u{23F3}'
+            | '\u{25FD}'..='\u{25FE}'
+            | '\u{2614}'..='\u{2615}'
+            | '\u{2648}'..='\u{2653}'
+            | '\u{267F}'
+            | '\u{2693}'
+            | '\u{26A1}'
+            | '\u{26AA}'..='\u{26AB}'
+            | '\u{26BD}'..='\u{26BE}'
+            | '\u{26C4}'..='\u{26C5}'
+            | '\u{26CE}'
+            | '\u{26D4}'
+            | '\u{26EA}'
+            | '\u{26F2}'..='\u{26F3}'
+            | '\u{26F5}'
+            | '\u{26FA}'
+            | '\u{26FD}'
+            | '\u{2705}'
+            | '\u{270A}'..='\u{270B}'
+            | '\u{2728}'
+            | '\u{274C}'
+            | '\u{274E}'
+            | '\u{2753}'..='\u{2755}'
+            | '\u{2757}'
+            | '\u{2795}'..='\u{2797}'
+            | '\u{27B0}'
+            | '\u{27BF}'
+            | '\u{2B1B}'..='\u{2B1C}'
+            | '\u{2B50}'
+            | '\u{2B55}'
+            | '\u{1F004}'
+            | '\u{1F0CF}'
+            | '\u{1F18E}'
+            | '\u{1F191}'..='\u{1F19A}'
+            | '\u{1F1E6}'..='\u{1F1FF}'
+            | '\u{1F201}'
+            | '\u{1F21A}'
+            | '\u{1F22F}'
+            | '\u{1F232}'..='\u{1F236}'
+            | '\u{1F238}'..='\u{1F23A}'
+            | '\u{1F250}'..='\u{1F251}'
+            | '\u{1F300}'..='\u{1F320}'
+            | '\u{1F32D}'..='\u{1F335}'
+            | '\u{1F337}'..='\u{1F37C}'
+            | '\u{1F37E}'..='\u{1F393}'
+            | '\u{1F3A0}'..='\u{1F3CA}'
+            | '\u{1F3CF}'..='\u{1F3D3}'
+            | '\u{1F3E0}'..='\u{1F3F0}'
+            | '\u{1F3F4}'
+            | '\u{1F3F8}'..='\u{1F43E}'
+            | '\u{1F440}'
+            | '\u{1F442}'..='\u{1F4FC}'
+            | '\u{1F4FF}'..='\u{1F53D}'
+            | '\u{1F54B}'..='\u{1F54E}'
+            | '\u{1F550}'..='\u{1F567}'
+            | '\u{1F57A}'
+            | '\u{1F595}'..='\u{1F596}'
+            | '\u{1F5A4}'
+            | '\u{1F5FB}'..='\u{1F64F}'
+            | '\u{1F680}'..='\u{1F6C5}'
+            | '\u{1F6CC}'
+            | '\u{1F6D0}'..='\u{1F6D2}'
+            | '\u{1F6D5}'..='\u{1F6D7}'
+            | '\u{1F6DC}'..='\u{1F6DF}'
+            | '\u{1F6EB}'..='\u{1F6EC}'
+            | '\u{1F6F4}'..='\u{1F6FC}'
+            | '\u{1F7E0}'..='\u{1F7EB}'
+            | '\u{1F7F0}'
+            | '\u{1F90C}'..='\u{1F93A}'
+            | '\u{1F93C}'..='\u{1F945}'
+            | '\u{1F947}'..='\u{1F9FF}'
+            | '\u{1FA70}'..='\u{1FA7C}'
+            | '\u{1FA80}'..='\u{1FA88}'
+            | '\u{1FA90}'..='\u{1FABD}'
+            | '\u{1FABF}'..='\u{1FAC5}'
+            | '\u{1FACE}'..='\u{1FADB}'
+            | '\u{1FAE0}'..='\u{1FAE8}'
+            | '\u{1FAF0}'..='\u{1FAF8}'
+    )
+}
+
+/// Check if a character has the Unicode 15.0 `Extended_Pictographic` property, which also
+/// covers characters shown as text unless followed by U+FE0F
+fn is_extended_pictographic(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00A9}'
+            | '\u{00AE}'
+            | '\u{203C}'
+            | '\u{2049}'
+            | '\u{2122}'
+            | '\u{2139}'
+            | '\u{2194}'..='\u{2199}'
+            | '\u{21A9}'..='\u{21AA}'
+            | '\u{231A}'..='\u{231B}'
+            | '\u{2328}'
+            | '\u{2388}'
+            | '\u{23CF}'
+            | '\u{23E9}'..='\u{23F3}'
+            | '\u{23F8}'..='\u{23FA}'
+            | '\u{24C2}'
+            | '\u{25AA}'..='\u{25AB}'
+            | '\u{25B6}'
+            | '\u{25C0}'
+            | '\u{25FB}'..='\u{25FE}'
+            | '\u{2600}'..='\u{2605}'
+            | '\u{2607}'..='\u{2612}'
+            | '\u{2614}'..='\u{2685}'
+            | '\u{2690}'..='\u{2705}'
+            | '\u{2708}'..='\u{2712}'
+            | '\u{2714}'
+            | '\u{2716}'
+            | '\u{271D}'
+            | '\u{2721}'
+            | '\u{2728}'
+            | '\u{2733}'..='\u{2734}'
+            | '\u{2744}'
+            | '\u{2747}'
+            | '\u{274C}'
+            | '\u{274E}'
+            | '\u{2753}'..='\u{2755}'
+            | '\u{2757}'
+            | '\u{2763}'..='\u{2767}'
+            | '\u{2795}'..='\u{2797}'
+            | '\u{27A1}'
+            | '\u{27B0}'
+            | '\u{27BF}'
+            | '\u{2934}'..='\u{2935}'
+            | '\u{2B05}'..='\u{2B07}'
+            | '\u{2B1B}'..='\u{2B1C}'
+            | '\u{2B50}'
+            | '\u{2B55}'
+            | '\u{3030}'
+            | '\u{303D}'
+            | '\u{3297}'
+            | '\u{3299}'
+            | '\u{1F000}'..='\u{1F0FF}'
+            | '\u{1F10D}'..='\u{1F10F}'
+            | '\u{1F12F}'
+            | '\u{1F16C}'..='\u{1F171}'
+            | '\u{1F17E}'..='\u{1F17F}'
+            | '\u{1F18E}'
+            | '\u{1F191}'..='\u{1F19A}'
+            | '\u{1F1AD}'..='\u{1F1E5}'
+            | '\u{1F201}'..='\u{1F20F}'
+            | '\u{1F21A}'
+            | '\u{1F22F}'
+            | '\u{1F232}'..='\u{1F23A}'
+            | '\u{1F23C}'..='\u{1F23F}'
+            | '\u{1F249}'..='\u{1F3FA}'
+            | '\u{1F400}'..='\u{1F53D}'
+            | '\u{1F546}'..='\u{1F64F}'
+            | '\u{1F680}'..='\u{1F6FF}'
+            | '\u{1F774}'..='\u{1F77F}'
+            | '\u{1F7D5}'..='\u{1F7FF}'
+            | '\u{1F80C}'..='\u{1F80F}'
+            | '\u{1F848}'..='\u{1F84F}'
+            | '\u{1F85A}'..='\u{1F85F}'
+            | '\u{1F888}'..='\u{1F88F}'
+            | '\u{1F8AE}'..='\u{1F8FF}'
+            | '\u{1F90C}'..='\u{1F93A}'
+            | '\u{1F93C}'..='\u{1F945}'
+            | '\u{1F947}'..='\u{1FAFF}'
+            | '\u{1FC00}'..='\u{1FFFD}'
+    )
+}
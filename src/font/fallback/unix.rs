@@ -24,6 +24,11 @@ pub fn common_fallback() -> &'static [&'static str] {
     ]
 }
 
+// Fallbacks to use before the common fallbacks when the text contains emoji
+pub fn emoji_fallback() -> &'static [&'static str] {
+    &["Noto Color Emoji"]
+}
+
 // Fallbacks to never use
 pub fn forbidden_fallback() -> &'static [&'static str] {
     &[]
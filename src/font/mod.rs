@@ -50,6 +50,65 @@ impl Font {
         self.rustybuzz.borrow_dependent()
     }
 
+    /// Get the glyph ID for a character, or None if the font does not contain it
+    pub fn glyph_id_for_char(&self, c: char) -> Option<u16> {
+        self.rustybuzz().glyph_index(c).map(|id| id.0)
+    }
+
+    /// Check if the font contains a glyph for a character
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.rustybuzz().glyph_index(c).is_some()
+    }
+
+    /// Get an iterator over all glyph IDs defined in the font
+    pub fn all_glyph_ids(&self) -> impl Iterator<Item = u16> {
+        0..self.rustybuzz().number_of_glyphs()
+    }
+
+    /// Get the cap height in pixels for the provided font size, or `0.7 * font_size` if the font
+    /// does not provide it
+    ///
+    /// Returns None if the font has no units per em.
+    pub fn cap_height_px(&self, font_size: f32) -> Option<f32> {
+        let face = self.rustybuzz();
+        self.scale_px(face.capital_height(), font_size, 0.7)
+    }
+
+    /// Get the x-height in pixels for the provided font size, or `0.5 * font_size` if the font
+    /// does not provide it
+    ///
+    /// Returns None if the font has no units per em.
+    pub fn x_height_px(&self, font_size: f32) -> Option<f32> {
+        let face = self.rustybuzz();
+        self.scale_px(face.x_height(), font_size, 0.5)
+    }
+
+    /// Get the line height in pixels for the provided font size, from the ascent, descent, and line
+    /// gap of the font
+    ///
+    /// Returns None if the font has no units per em.
+    pub fn line_height_px(&self, font_size: f32) -> Option<f32> {
+        let face = self.rustybuzz();
+        let units_per_em = face.units_per_em();
+        if units_per_em == 0 {
+            return None;
+        }
+        let units =
+            i32::from(face.ascender()) - i32::from(face.descender()) + i32::from(face.line_gap());
+        Some(units as f32 * font_size / f32::from(units_per_em))
+    }
+
+    fn scale_px(&self, units_opt: Option<i16>, font_size: f32, fallback: f32) -> Option<f32> {
+        let units_per_em = self.rustybuzz().units_per_em();
+        if units_per_em == 0 {
+            return None;
+        }
+        Some(match units_opt {
+            Some(units) => f32::from(units) * font_size / f32::from(units_per_em),
+            None => fallback * font_size,
+        })
+    }
+
     #[cfg(feature = "swash")]
     pub fn as_swash(&self) -> swash::FontRef<'_> {
         let swash = &self.swash;
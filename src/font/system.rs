@@ -1,4 +1,6 @@
-use crate::{Attrs, AttrsOwned, Font};
+use crate::{Attrs, AttrsOwned, Font, Stretch, Style, Weight};
+#[cfg(feature = "subset")]
+use alloc::collections::BTreeSet;
 use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
@@ -16,6 +18,66 @@ type HashMap<K, V> = hashbrown::HashMap<K, V, BuildHasher>;
 pub use fontdb;
 pub use rustybuzz;
 
+/// Information about a font face, see [`FontSystem::list_fonts`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FontInfo {
+    /// The ID of the face in the database
+    pub id: fontdb::ID,
+    /// The family name, in English if available
+    pub family: String,
+    /// The style name, like `Italic`
+    pub style: String,
+    /// The weight
+    pub weight: Weight,
+    /// The stretch
+    pub stretch: Stretch,
+    /// Whether all glyphs have the same advance
+    pub is_monospace: bool,
+}
+
+/// A font embedded in the application, see [`FontSystem::with_embedded_fonts`]
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddedFont {
+    /// The font data, like the result of `include_bytes!`
+    pub data: &'static [u8],
+    /// Use the family of this font for [`fontdb::Family::SansSerif`]
+    pub sans_serif: bool,
+    /// Use the family of this font for [`fontdb::Family::Serif`]
+    pub serif: bool,
+    /// Use the family of this font for [`fontdb::Family::Monospace`]
+    pub monospace: bool,
+}
+
+impl EmbeddedFont {
+    /// Create a new [`EmbeddedFont`] that is not used for any generic family
+    pub const fn new(data: &'static [u8]) -> Self {
+        Self {
+            data,
+            sans_serif: false,
+            serif: false,
+            monospace: false,
+        }
+    }
+
+    /// Use the family of this font for [`fontdb::Family::SansSerif`]
+    pub const fn sans_serif(mut self) -> Self {
+        self.sans_serif = true;
+        self
+    }
+
+    /// Use the family of this font for [`fontdb::Family::Serif`]
+    pub const fn serif(mut self) -> Self {
+        self.serif = true;
+        self
+    }
+
+    /// Use the family of this font for [`fontdb::Family::Monospace`]
+    pub const fn monospace(mut self) -> Self {
+        self.monospace = true;
+        self
+    }
+}
+
 /// Access to the system fonts.
 pub struct FontSystem {
     /// The locale of the system.
@@ -29,6 +91,9 @@ pub struct FontSystem {
 
     /// Cache for font matches.
     font_matches_cache: HashMap<AttrsOwned, Arc<Vec<fontdb::ID>>>,
+
+    /// Incremented whenever the database may have changed, to invalidate shapes cached outside.
+    db_generation: u64,
 }
 
 impl fmt::Debug for FontSystem {
@@ -69,6 +134,70 @@ impl FontSystem {
         Self::new_with_locale_and_db(locale, db)
     }
 
+    /// Create a new [`FontSystem`] with only the provided embedded fonts
+    ///
+    /// System fonts are not loaded and the filesystem is never accessed, so this works without
+    /// the `std` feature. The font data is used without copying it. Generic families that no
+    /// font is marked for default to the family of the first font.
+    ///
+    /// ```
+    /// # use cosmic_text::{EmbeddedFont, FontSystem};
+    /// let font_system = FontSystem::with_embedded_fonts([
+    ///     EmbeddedFont::new(include_bytes!("../../fonts/NotoSans-Regular.ttf"))
+    ///         .sans_serif()
+    ///         .serif(),
+    ///     EmbeddedFont::new(include_bytes!("../../fonts/FiraMono-Medium.ttf")).monospace(),
+    /// ]);
+    /// assert_eq!(font_system.db().len(), 2);
+    /// ```
+    pub fn with_embedded_fonts(fonts: impl IntoIterator<Item = EmbeddedFont>) -> Self {
+        let mut db = fontdb::Database::new();
+        let mut first_family_opt = None;
+        let (mut sans_serif, mut serif, mut monospace) = (false, false, false);
+        for font in fonts {
+            let ids = db.load_font_source(fontdb::Source::Binary(Arc::new(font.data)));
+            let family_opt = ids
+                .first()
+                .and_then(|id| db.face(*id))
+                .and_then(|face| face.families.first())
+                .map(|(name, _)| name.clone());
+            let family = match family_opt {
+                Some(some) => some,
+                None => {
+                    log::warn!("failed to load embedded font");
+                    continue;
+                }
+            };
+            if font.sans_serif {
+                db.set_sans_serif_family(family.as_str());
+                sans_serif = true;
+            }
+            if font.serif {
+                db.set_serif_family(family.as_str());
+                serif = true;
+            }
+            if font.monospace {
+                db.set_monospace_family(family.as_str());
+                monospace = true;
+            }
+            first_family_opt.get_or_insert(family);
+        }
+
+        if let Some(first_family) = first_family_opt {
+            if !sans_serif {
+                db.set_sans_serif_family(first_family.as_str());
+            }
+            if !serif {
+                db.set_serif_family(first_family.as_str());
+            }
+            if !monospace {
+                db.set_monospace_family(first_family);
+            }
+        }
+
+        Self::new_with_locale_and_db(Self::get_locale(), db)
+    }
+
     /// Create a new [`FontSystem`] with a pre-specified locale and font database.
     pub fn new_with_locale_and_db(locale: String, db: fontdb::Database) -> Self {
         Self {
@@ -76,6 +205,7 @@ impl FontSystem {
             db,
             font_cache: HashMap::default(),
             font_matches_cache: HashMap::default(),
+            db_generation: 0,
         }
     }
 
@@ -90,11 +220,109 @@ impl FontSystem {
     }
 
     /// Get a mutable reference to the database.
+    ///
+    /// This can be used to add or remove fonts after construction, for example after
+    /// [`FontSystem::new_with_locale_and_db`]. Cached font matches are cleared, so changes are
+    /// picked up by the next shaping.
     pub fn db_mut(&mut self) -> &mut fontdb::Database {
         self.font_matches_cache.clear();
+        self.db_generation += 1;
         &mut self.db
     }
 
+    /// Get a number that changes whenever fonts may have been added or removed
+    pub(crate) fn db_generation(&self) -> u64 {
+        self.db_generation
+    }
+
+    /// Load fonts from static data, like data embedded with `include_bytes!`, without copying it
+    ///
+    /// Returns the IDs of the loaded faces.
+    pub fn load_font_static(&mut self, data: &'static [u8]) -> Vec<fontdb::ID> {
+        self.db_mut()
+            .load_font_source(fontdb::Source::Binary(Arc::new(data)))
+            .into_iter()
+            .collect()
+    }
+
+    /// Get the IDs of all faces in the database.
+    pub fn list_font_ids(&self) -> Vec<fontdb::ID> {
+        self.db.faces().map(|face| face.id).collect()
+    }
+
+    /// Get information about all faces in the database, for example to show in a font picker
+    pub fn list_fonts(&self) -> Vec<FontInfo> {
+        self.db
+            .faces()
+            .map(|face| FontInfo {
+                id: face.id,
+                family: face
+                    .families
+                    .first()
+                    .map_or_else(String::new, |(name, _)| name.clone()),
+                style: String::from(match face.style {
+                    Style::Normal => "Normal",
+                    Style::Italic => "Italic",
+                    Style::Oblique => "Oblique",
+                }),
+                weight: face.weight,
+                stretch: face.stretch,
+                is_monospace: face.monospaced,
+            })
+            .collect()
+    }
+
+    /// Get the sorted family names of all faces in the database, without duplicates
+    pub fn list_families(&self) -> Vec<String> {
+        let mut families: Vec<String> = self
+            .db
+            .faces()
+            .filter_map(|face| face.families.first())
+            .map(|(name, _)| name.clone())
+            .collect();
+        families.sort_unstable();
+        families.dedup();
+        families
+    }
+
+    /// Remove a font from the database and the caches, so it is no longer returned by
+    /// [`FontSystem::get_font`] or used for shaping
+    ///
+    /// The database and caches hold the only references to the font data owned by the
+    /// [`FontSystem`], so removing a font frees its data unless it is still in use. Any
+    /// [`Font`] already returned by [`FontSystem::get_font`] stays valid, as it holds its own
+    /// reference to the data, which is freed when the last of them is dropped. Lines that were
+    /// already shaped with the font keep its glyphs until they are shaped again.
+    pub fn remove_font(&mut self, id: fontdb::ID) {
+        self.db.remove_face(id);
+        self.font_cache.remove(&id);
+        self.font_matches_cache.retain(|_, ids| !ids.contains(&id));
+        self.db_generation += 1;
+    }
+
+    /// Get the IDs of all faces in the database that have a glyph for a character, for example
+    /// to implement custom fallback or a character map.
+    ///
+    /// Faces that were already loaded are checked using the cached [`Font`], other faces are
+    /// parsed from the database without being added to the cache.
+    pub fn query_coverage(&self, c: char) -> Vec<fontdb::ID> {
+        self.db
+            .faces()
+            .filter(|face| match self.font_cache.get(&face.id) {
+                Some(Some(font)) => font.has_glyph(c),
+                Some(None) => false,
+                None => self
+                    .db
+                    .with_face_data(face.id, |data, index| {
+                        rustybuzz::ttf_parser::Face::parse(data, index)
+                            .map_or(false, |face| face.glyph_index(c).is_some())
+                    })
+                    .unwrap_or(false),
+            })
+            .map(|face| face.id)
+            .collect()
+    }
+
     /// Consume this [`FontSystem`] and return the locale and database.
     pub fn into_locale_and_db(self) -> (String, fontdb::Database) {
         (self.locale, self.db)
@@ -121,10 +349,31 @@ impl FontSystem {
             .clone()
     }
 
+    /// Subset a font so it only contains the glyphs for the provided characters, returning the
+    /// new font data
+    #[cfg(feature = "subset")]
+    pub fn subset_font(&mut self, id: fontdb::ID, chars: &BTreeSet<char>) -> Option<Vec<u8>> {
+        let index = self.db.face(id)?.index;
+        let font = self.get_font(id)?;
+
+        // Always keep the .notdef glyph
+        let mut glyph_ids = Vec::with_capacity(chars.len() + 1);
+        glyph_ids.push(0);
+        glyph_ids.extend(chars.iter().filter_map(|c| font.glyph_id_for_char(*c)));
+
+        match subsetter::subset(font.data(), index, subsetter::Profile::pdf(&glyph_ids)) {
+            Ok(data) => Some(data),
+            Err(err) => {
+                log::warn!("failed to subset font {:?}: {:?}", id, err);
+                None
+            }
+        }
+    }
+
     pub fn get_font_matches(&mut self, attrs: Attrs<'_>) -> Arc<Vec<fontdb::ID>> {
         self.font_matches_cache
             //TODO: do not create AttrsOwned unless entry does not already exist
-            .entry(AttrsOwned::new(attrs))
+            .entry(AttrsOwned::new(attrs.clone()))
             .or_insert_with(|| {
                 #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
                 let now = std::time::Instant::now();
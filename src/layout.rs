@@ -1,13 +1,17 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use alloc::sync::Arc;
 use core::fmt::Display;
 
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
-use crate::{CacheKey, Color};
+use crate::{CacheKey, Color, FontVariations, TextDecoration};
 
 /// A laid out glyph
+///
+/// All fields are public so that external renderers can draw glyphs without going through
+/// `SwashCache` or other helpers of this crate.
 #[derive(Debug)]
 pub struct LayoutGlyph {
     /// Start index of cluster in original line
@@ -18,13 +22,13 @@ pub struct LayoutGlyph {
     pub font_size: f32,
     /// Font id of the glyph
     pub font_id: fontdb::ID,
-    /// Font id of the glyph
+    /// Glyph id of the glyph in the font given by `font_id`
     pub glyph_id: u16,
-    /// X offset of hitbox
+    /// X offset of hitbox, relative to the start of the line
     pub x: f32,
-    /// Y offset of hitbox
+    /// Y offset of hitbox, relative to the baseline of the line
     pub y: f32,
-    /// Width of hitbox
+    /// Width of hitbox, which is the advance of the glyph
     pub w: f32,
     /// Unicode BiDi embedding level, character is left-to-right if `level` is divisible by 2
     pub level: unicode_bidi::Level,
@@ -46,15 +50,26 @@ pub struct LayoutGlyph {
     /// cannot guarantee pixel grid alignment. For instance, when you want to use the glyphs
     /// for vectorial text, apply linear transformations to the layout, etc.
     pub y_offset: f32,
-    /// Optional color override
+    /// Optional color override, the default text color should be used if this is `None`
     pub color_opt: Option<Color>,
     /// Metadata from `Attrs`
     pub metadata: usize,
+    /// Text decoration from `Attrs`
+    pub text_decoration: TextDecoration,
+    /// Optional color override for decorations like underline
+    pub decoration_color_opt: Option<Color>,
+    /// Optional background color
+    pub background_opt: Option<Color>,
+    /// Variable font axes from `Attrs`, which must also be used for rendering
+    pub font_variations: Option<Arc<FontVariations>>,
 }
 
+/// A glyph positioned on the physical pixel grid, returned by [`LayoutGlyph::physical`]
 #[derive(Debug)]
 pub struct PhysicalGlyph {
     /// Cache key, see [CacheKey]
+    ///
+    /// This contains the fractional component of the glyph position, for subpixel rendering.
     pub cache_key: CacheKey,
     /// Integer component of X offset in line
     pub x: i32,
@@ -63,11 +78,13 @@ pub struct PhysicalGlyph {
 }
 
 impl LayoutGlyph {
+    /// Get the [`PhysicalGlyph`] for rendering this glyph at `offset`, with the layout scaled
+    /// by `scale`
     pub fn physical(&self, offset: (f32, f32), scale: f32) -> PhysicalGlyph {
         let x_offset = self.font_size * self.x_offset;
         let y_offset = self.font_size * self.y_offset;
 
-        let (cache_key, x, y) = CacheKey::new(
+        let (mut cache_key, x, y) = CacheKey::new(
             self.font_id,
             self.glyph_id,
             self.font_size * scale,
@@ -76,15 +93,19 @@ impl LayoutGlyph {
                 libm::truncf((self.y - y_offset) * scale + offset.1), // Hinting in Y axis
             ),
         );
+        cache_key.font_variations = self.font_variations.clone();
 
         PhysicalGlyph { cache_key, x, y }
     }
 }
 
 /// A line of laid out glyphs
+///
+/// Returned by [`crate::BufferLine::layout_opt`] and [`crate::BufferLine::layout`], one for each
+/// visual line after wrapping. All fields are public for use by external renderers.
 #[derive(Debug)]
 pub struct LayoutLine {
-    /// Width of the line
+    /// Width of the line, including whitespace
     pub w: f32,
     /// Maximum ascent of the glyphs in line
     pub max_ascent: f32,
@@ -94,15 +115,75 @@ pub struct LayoutLine {
     pub glyphs: Vec<LayoutGlyph>,
 }
 
+impl LayoutLine {
+    /// Remove glyphs from the end of the line until it fits in `width` followed by the glyphs of
+    /// `ellipsis`, which must be laid out starting at zero. The remaining glyphs are moved to the
+    /// start edge of the line.
+    pub(crate) fn truncate_with_ellipsis(&mut self, width: f32, rtl: bool, ellipsis: LayoutLine) {
+        let available = width - ellipsis.w;
+        let mut x = 0.0;
+        let mut keep = 0;
+        for glyph in self.glyphs.iter() {
+            if x + glyph.w > available {
+                break;
+            }
+            x += glyph.w;
+            keep += 1;
+        }
+        self.glyphs.truncate(keep);
+
+        let mut x = 0.0;
+        for glyph in self.glyphs.iter_mut() {
+            glyph.x = if rtl { width - x - glyph.w } else { x };
+            x += glyph.w;
+        }
+
+        // The ellipsis does not cover any text, so hits on it place the cursor at the cut
+        let index = self.glyphs.last().map_or(0, |glyph| glyph.end);
+        for mut glyph in ellipsis.glyphs {
+            glyph.start = index;
+            glyph.end = index;
+            glyph.x += if rtl { width - x - ellipsis.w } else { x };
+            self.glyphs.push(glyph);
+        }
+
+        self.w = x + ellipsis.w;
+        self.max_ascent = self.max_ascent.max(ellipsis.max_ascent);
+        self.max_descent = self.max_descent.max(ellipsis.max_descent);
+    }
+}
+
+/// A rectangle, in pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    /// X offset of the left edge
+    pub x: f32,
+    /// Y offset of the top edge
+    pub y: f32,
+    /// Width
+    pub w: f32,
+    /// Height
+    pub h: f32,
+}
+
+impl Rect {
+    pub const fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self { x, y, w, h }
+    }
+}
+
 /// Wrapping mode
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum Wrap {
     /// No wrapping
     None,
-    /// Wraps at a glyph level
+    /// Wraps at a glyph level, at the width regardless of word boundaries
     Glyph,
     /// Word Wrapping
     Word,
+    /// Wraps at a word level, falling back to glyph level for words wider than a line, like
+    /// CSS `overflow-wrap: break-word`
+    WordOrGlyph,
 }
 
 impl Display for Wrap {
@@ -111,13 +192,31 @@ impl Display for Wrap {
             Self::None => write!(f, "No Wrap"),
             Self::Word => write!(f, "Word Wrap"),
             Self::Glyph => write!(f, "Character"),
+            Self::WordOrGlyph => write!(f, "Word Wrap or Character"),
         }
     }
 }
 
-/// Align or justify
+/// Handling of lines that are wider than the buffer when wrapping is disabled
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Overflow {
+    /// Lines extend past the width, to be clipped by the renderer
+    Clip,
+    /// Lines are truncated to the width, ending with an ellipsis
+    Ellipsis,
+}
+
+impl Default for Overflow {
+    fn default() -> Self {
+        Overflow::Clip
+    }
+}
+
+/// Align or justify
+#[derive(Debug, Eq, Hash, PartialEq, Clone, Copy)]
 pub enum Align {
+    /// Align to the start of the paragraph, left for LTR and right for RTL paragraphs
+    Start,
     Left,
     Right,
     Center,
@@ -128,6 +227,7 @@ pub enum Align {
 impl Display for Align {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
+            Self::Start => write!(f, "Start"),
             Self::Left => write!(f, "Left"),
             Self::Right => write!(f, "Right"),
             Self::Center => write!(f, "Center"),
@@ -136,3 +236,58 @@ impl Display for Align {
         }
     }
 }
+
+/// Base direction of a paragraph
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BaseDirection {
+    /// Detect direction from the first strong character of the paragraph
+    Auto,
+    /// Left-to-right
+    LeftToRight,
+    /// Right-to-left
+    RightToLeft,
+}
+
+impl Default for BaseDirection {
+    fn default() -> Self {
+        BaseDirection::Auto
+    }
+}
+
+impl BaseDirection {
+    /// Get the paragraph level to use for bidirectional analysis, or None to detect it
+    pub(crate) fn default_para_level(self) -> Option<unicode_bidi::Level> {
+        match self {
+            BaseDirection::Auto => None,
+            BaseDirection::LeftToRight => Some(unicode_bidi::Level::ltr()),
+            BaseDirection::RightToLeft => Some(unicode_bidi::Level::rtl()),
+        }
+    }
+}
+
+/// Unicode bidirectional analysis of a line
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BidiInfo {
+    /// Embedding level of the first paragraph in the line, even for left-to-right and odd for
+    /// right-to-left
+    pub paragraph_level: u8,
+    /// Embedding level of each character, in logical order
+    pub levels: Vec<u8>,
+    /// Byte index of each character, in visual order
+    pub visual_order: Vec<usize>,
+}
+
+/// Formatting that applies to a whole paragraph rather than to spans of characters
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ParagraphStyle {
+    /// Alignment of the paragraph, overriding the alignment of the line if set
+    pub alignment: Option<Align>,
+    /// Indentation from the start edge of the paragraph, in pixels
+    pub indent: f32,
+    /// Space added above the paragraph, in pixels
+    pub spacing_before: f32,
+    /// Space added below the paragraph, in pixels
+    pub spacing_after: f32,
+    /// Base direction of the paragraph
+    pub direction: BaseDirection,
+}
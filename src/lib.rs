@@ -111,6 +111,11 @@ mod buffer_line;
 pub use self::cache::*;
 mod cache;
 
+pub mod color;
+
+pub use self::document::*;
+mod document;
+
 pub use self::edit::*;
 mod edit;
 
@@ -2,17 +2,22 @@
 
 #![allow(clippy::too_many_arguments)]
 
+use alloc::sync::Arc;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 use core::cmp::{max, min};
 use core::fmt;
 use core::mem;
 use core::ops::Range;
+use rustybuzz::ttf_parser::Tag;
 use unicode_script::{Script, UnicodeScript};
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::fallback::FontFallbackIter;
-use crate::{Align, AttrsList, Color, Font, FontSystem, LayoutGlyph, LayoutLine, Wrap};
+use crate::{
+    Align, AttrsList, BaseDirection, Color, Font, FontSystem, FontVariations, LayoutGlyph,
+    LayoutLine, TextDecoration, Wrap,
+};
 
 /// The shaping strategy of some text.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -110,7 +115,36 @@ fn shape_fallback(
     let rtl = matches!(buffer.direction(), rustybuzz::Direction::RightToLeft);
     assert_eq!(rtl, span_rtl);
 
-    let glyph_buffer = rustybuzz::shape(font.rustybuzz(), &[], buffer);
+    let run_attrs = attrs_list.get_span(start_run);
+    let mut features = Vec::new();
+    if !run_attrs.kerning {
+        features.push(rustybuzz::Feature::new(Tag::from_bytes(b"kern"), 0, ..));
+    }
+    if !run_attrs.ligatures {
+        features.push(rustybuzz::Feature::new(Tag::from_bytes(b"liga"), 0, ..));
+        features.push(rustybuzz::Feature::new(Tag::from_bytes(b"clig"), 0, ..));
+    }
+    if let Some(font_features) = &run_attrs.font_features {
+        for (tag, value) in font_features.iter() {
+            features.push(rustybuzz::Feature::new(Tag::from_bytes(&tag.0), *value, ..));
+        }
+    }
+
+    let glyph_buffer = match &run_attrs.font_variations {
+        Some(font_variations) => {
+            let variations: Vec<rustybuzz::Variation> = font_variations
+                .iter()
+                .map(|(tag, value)| rustybuzz::Variation {
+                    tag: Tag::from_bytes(&tag.0),
+                    value,
+                })
+                .collect();
+            let mut face = font.rustybuzz().clone();
+            face.set_variations(&variations);
+            rustybuzz::shape(&face, &features, buffer)
+        }
+        None => rustybuzz::shape(font.rustybuzz(), &features, buffer),
+    };
     let glyph_infos = glyph_buffer.glyph_infos();
     let glyph_positions = glyph_buffer.glyph_positions();
 
@@ -144,6 +178,12 @@ fn shape_fallback(
             //TODO: color should not be related to shaping
             color_opt: attrs.color_opt,
             metadata: attrs.metadata,
+            text_decoration: attrs.text_decoration,
+            decoration_color_opt: attrs.decoration_color_opt,
+            background_opt: attrs.background_opt,
+            font_variations: attrs.font_variations.clone(),
+            spacing: 0.0,
+            tab: false,
         });
     }
 
@@ -209,10 +249,16 @@ fn shape_run(
 
     let attrs = attrs_list.get_span(start_run);
 
-    let fonts = font_system.get_font_matches(attrs);
+    let fonts = font_system.get_font_matches(attrs.clone());
 
     let default_families = [&attrs.family];
-    let mut font_iter = FontFallbackIter::new(font_system, &fonts, &default_families, &scripts);
+    let mut font_iter = FontFallbackIter::new(
+        font_system,
+        &fonts,
+        &default_families,
+        &scripts,
+        &line[start_run..end_run],
+    );
 
     let font = font_iter.next().expect("no default font found");
 
@@ -322,10 +368,16 @@ fn shape_skip(
     end_run: usize,
 ) {
     let attrs = attrs_list.get_span(start_run);
-    let fonts = font_system.get_font_matches(attrs);
+    let fonts = font_system.get_font_matches(attrs.clone());
 
     let default_families = [&attrs.family];
-    let mut font_iter = FontFallbackIter::new(font_system, &fonts, &default_families, &[]);
+    let mut font_iter = FontFallbackIter::new(
+        font_system,
+        &fonts,
+        &default_families,
+        &[],
+        &line[start_run..end_run],
+    );
 
     let font = font_iter.next().expect("no default font found");
     let font_id = font.id();
@@ -343,8 +395,13 @@ fn shape_skip(
             .chars()
             .enumerate()
             .map(|(i, codepoint)| {
-                let glyph_id = charmap.map(codepoint);
-                let x_advance = glyph_metrics.advance_width(glyph_id);
+                // Soft hyphens are only shown at line breaks, see `ShapeWord::hyphen_opt`
+                let (glyph_id, x_advance) = if codepoint == '\u{AD}' {
+                    (charmap.map(' '), 0.0)
+                } else {
+                    let glyph_id = charmap.map(codepoint);
+                    (glyph_id, glyph_metrics.advance_width(glyph_id))
+                };
 
                 ShapeGlyph {
                     start: i,
@@ -359,13 +416,28 @@ fn shape_skip(
                     glyph_id,
                     color_opt: attrs.color_opt,
                     metadata: attrs.metadata,
+                    text_decoration: attrs.text_decoration,
+                    decoration_color_opt: attrs.decoration_color_opt,
+                    background_opt: attrs.background_opt,
+                    font_variations: attrs.font_variations.clone(),
+                    spacing: 0.0,
+                    tab: false,
                 }
             }),
     );
 }
 
+/// Find the next of the sorted `tab_stops` after `x`, or else the next multiple of `tab_width`
+fn next_tab_stop(x: f32, tab_stops: &[f32], tab_width: f32) -> Option<f32> {
+    tab_stops
+        .iter()
+        .copied()
+        .find(|&stop| stop > x)
+        .or_else(|| (tab_width > 0.0).then(|| (libm::floorf(x / tab_width) + 1.0) * tab_width))
+}
+
 /// A shaped glyph
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ShapeGlyph {
     pub start: usize,
     pub end: usize,
@@ -379,9 +451,34 @@ pub struct ShapeGlyph {
     pub glyph_id: u16,
     pub color_opt: Option<Color>,
     pub metadata: usize,
+    pub text_decoration: TextDecoration,
+    pub decoration_color_opt: Option<Color>,
+    pub background_opt: Option<Color>,
+    /// Variable font axes used to shape this glyph
+    pub font_variations: Option<Arc<FontVariations>>,
+    /// Extra advance after this glyph in pixels, from letter and word spacing
+    pub spacing: f32,
+    /// True if this glyph is for a tab, which advances to the next tab stop during layout
+    pub tab: bool,
 }
 
 impl ShapeGlyph {
+    /// Get the advance of this glyph in pixels, including letter and word spacing
+    pub fn width(&self, font_size: f32) -> f32 {
+        font_size * self.x_advance + self.spacing
+    }
+
+    /// Get the advance of this glyph in pixels when it starts `x` pixels from the start edge of
+    /// the line, moving tabs to the next tab stop
+    fn width_at(&self, font_size: f32, x: f32, tab_stops: &[f32], tab_width: f32) -> f32 {
+        if self.tab {
+            if let Some(stop) = next_tab_stop(x, tab_stops, tab_width) {
+                return stop - x;
+            }
+        }
+        self.width(font_size)
+    }
+
     fn layout(
         &self,
         font_size: f32,
@@ -404,20 +501,46 @@ impl ShapeGlyph {
             y_offset: self.y_offset,
             color_opt: self.color_opt,
             metadata: self.metadata,
+            text_decoration: self.text_decoration,
+            decoration_color_opt: self.decoration_color_opt,
+            background_opt: self.background_opt,
+            font_variations: self.font_variations.clone(),
         }
     }
 }
 
 /// A shaped word (for word wrapping)
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ShapeWord {
     pub blank: bool,
     pub glyphs: Vec<ShapeGlyph>,
     pub x_advance: f32,
     pub y_advance: f32,
+    /// Extra advance of all glyphs in pixels, from letter and word spacing
+    pub spacing: f32,
+    /// Hyphen to show if a line is broken after this word, which ends with a soft hyphen
+    pub hyphen_opt: Option<ShapeGlyph>,
+    /// True if any glyph of this word is a tab
+    pub tab: bool,
 }
 
 impl ShapeWord {
+    /// Get the advance of this word in pixels, including letter and word spacing
+    pub fn width(&self, font_size: f32) -> f32 {
+        font_size * self.x_advance + self.spacing
+    }
+
+    /// Get the advance of this word in pixels when it starts `x` pixels from the start edge of
+    /// the line, moving tabs to the next tab stop
+    fn width_at(&self, font_size: f32, x: f32, tab_stops: &[f32], tab_width: f32) -> f32 {
+        if !self.tab {
+            return self.width(font_size);
+        }
+        self.glyphs.iter().fold(0.0, |w, glyph| {
+            w + glyph.width_at(font_size, x + w, tab_stops, tab_width)
+        })
+    }
+
     pub fn new(
         font_system: &mut FontSystem,
         line: &str,
@@ -498,22 +621,59 @@ impl ShapeWord {
 
         let mut x_advance = 0.0;
         let mut y_advance = 0.0;
-        for glyph in &glyphs {
+        let mut spacing = 0.0;
+        let mut tab = false;
+        for glyph in &mut glyphs {
+            glyph.tab = &line[glyph.start..glyph.end] == "\t";
+            tab |= glyph.tab;
+            let attrs = attrs_list.get_span(glyph.start);
+            glyph.spacing = attrs.get_letter_spacing();
+            if blank {
+                glyph.spacing += attrs.get_word_spacing();
+            }
             x_advance += glyph.x_advance;
             y_advance += glyph.y_advance;
+            spacing += glyph.spacing;
         }
 
+        let hyphen_opt = if !blank && word.ends_with('\u{AD}') {
+            let start = word_range.end - '\u{AD}'.len_utf8();
+            let attrs = attrs_list.get_span(start);
+            let mut hyphen_glyphs = Vec::with_capacity(1);
+            shaping.run(
+                scratch,
+                &mut hyphen_glyphs,
+                font_system,
+                "-",
+                &AttrsList::new(attrs.clone()),
+                0,
+                1,
+                span_rtl,
+            );
+            hyphen_glyphs.into_iter().next().map(|mut glyph| {
+                glyph.start = start;
+                glyph.end = word_range.end;
+                glyph.spacing = attrs.get_letter_spacing();
+                glyph
+            })
+        } else {
+            None
+        };
+
         Self {
             blank,
             glyphs,
             x_advance,
             y_advance,
+            spacing,
+            hyphen_opt,
+            tab,
         }
     }
 }
 
 /// A shaped span (for bidirectional processing)
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ShapeSpan {
     pub level: unicode_bidi::Level,
     pub words: Vec<ShapeWord>,
@@ -624,7 +784,7 @@ impl ShapeSpan {
 }
 
 /// A shaped line (or paragraph)
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ShapeLine {
     pub rtl: bool,
     pub spans: Vec<ShapeSpan>,
@@ -638,6 +798,8 @@ struct VisualLine {
     ranges: Vec<VlRange>,
     spaces: u32,
     w: f32,
+    /// Span and word index of the word whose hyphen ends this line
+    hyphen_opt: Option<(usize, usize)>,
 }
 
 impl ShapeLine {
@@ -670,10 +832,33 @@ impl ShapeLine {
         line: &str,
         attrs_list: &AttrsList,
         shaping: Shaping,
+    ) -> Self {
+        Self::new_in_buffer_with_direction(
+            scratch,
+            font_system,
+            line,
+            attrs_list,
+            shaping,
+            BaseDirection::Auto,
+        )
+    }
+
+    /// Shape a line into a set of spans with the provided base direction, using a scratch buffer.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `line` contains more than one paragraph.
+    pub fn new_in_buffer_with_direction(
+        scratch: &mut ShapeBuffer,
+        font_system: &mut FontSystem,
+        line: &str,
+        attrs_list: &AttrsList,
+        shaping: Shaping,
+        direction: BaseDirection,
     ) -> Self {
         let mut spans = Vec::new();
 
-        let bidi = unicode_bidi::BidiInfo::new(line, None);
+        let bidi = unicode_bidi::BidiInfo::new(line, direction.default_para_level());
         let rtl = if bidi.paragraphs.is_empty() {
             false
         } else {
@@ -874,6 +1059,35 @@ impl ShapeLine {
         wrap: Wrap,
         align: Option<Align>,
         layout_lines: &mut Vec<LayoutLine>,
+    ) {
+        self.layout_to_buffer_with_tabs(
+            scratch,
+            font_size,
+            line_width,
+            wrap,
+            align,
+            &[],
+            0.0,
+            layout_lines,
+        );
+    }
+
+    /// Layout to a buffer, moving tabs to the next of the sorted `tab_stops`, or else to the next
+    /// multiple of `tab_width`, both in pixels from the start edge of the text of each layout
+    /// line. Tabs keep their advance if there is no tab stop after them and `tab_width` is zero.
+    ///
+    /// Tab advances are known while wrapping, so lines with tabs still fit in `line_width`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn layout_to_buffer_with_tabs(
+        &self,
+        scratch: &mut ShapeBuffer,
+        font_size: f32,
+        line_width: f32,
+        wrap: Wrap,
+        align: Option<Align>,
+        tab_stops: &[f32],
+        tab_width: f32,
+        layout_lines: &mut Vec<LayoutLine>,
     ) {
         // For each visual line a list of  (span index,  and range of words in that span)
         // Note that a BiDi visual line could have multiple spans or parts of them
@@ -912,7 +1126,12 @@ impl ShapeLine {
                 let mut word_range_width = 0.;
                 let mut number_of_blanks: u32 = 0;
                 for word in span.words.iter() {
-                    let word_width = font_size * word.x_advance;
+                    let word_width = word.width_at(
+                        font_size,
+                        current_visual_line.w + word_range_width,
+                        tab_stops,
+                        tab_width,
+                    );
                     word_range_width += word_width;
                     if word.blank {
                         number_of_blanks += 1;
@@ -938,7 +1157,12 @@ impl ShapeLine {
                     // incongruent directions
                     let mut fitting_start = (span.words.len(), 0);
                     for (i, word) in span.words.iter().enumerate().rev() {
-                        let word_width = font_size * word.x_advance;
+                        let word_width = word.width_at(
+                            font_size,
+                            current_visual_line.w + word_range_width,
+                            tab_stops,
+                            tab_width,
+                        );
 
                         // Addition in the same order used to compute the final width, so that
                         // relayouts with that width as the `line_width` will produce the same
@@ -957,9 +1181,17 @@ impl ShapeLine {
                             }
                             word_range_width += word_width;
                             continue;
-                        } else if wrap == Wrap::Glyph {
+                        } else if wrap == Wrap::Glyph
+                            // Fall back to glyph wrapping if the word does not fit on its own line
+                            || (wrap == Wrap::WordOrGlyph && word_width > line_width)
+                        {
                             for (glyph_i, glyph) in word.glyphs.iter().enumerate().rev() {
-                                let glyph_width = font_size * glyph.x_advance;
+                                let glyph_width = glyph.width_at(
+                                    font_size,
+                                    current_visual_line.w + word_range_width,
+                                    tab_stops,
+                                    tab_width,
+                                );
                                 if current_visual_line.w + (word_range_width + glyph_width)
                                     <= line_width
                                 {
@@ -978,7 +1210,9 @@ impl ShapeLine {
                                     current_visual_line = VisualLine::default();
 
                                     number_of_blanks = 0;
-                                    word_range_width = glyph_width;
+                                    // Tabs are measured again from the start of the new line
+                                    word_range_width =
+                                        glyph.width_at(font_size, 0.0, tab_stops, tab_width);
                                     fitting_start = (i, glyph_i + 1);
                                 }
                             }
@@ -1025,7 +1259,9 @@ impl ShapeLine {
                                 word_range_width = 0.;
                                 fitting_start = (i, 0);
                             } else {
-                                word_range_width = word_width;
+                                // Tabs are measured again from the start of the new line
+                                word_range_width =
+                                    word.width_at(font_size, 0.0, tab_stops, tab_width);
                                 fitting_start = (i + 1, 0);
                             }
                         }
@@ -1042,8 +1278,18 @@ impl ShapeLine {
                     // congruent direction
                     let mut fitting_start = (0, 0);
                     for (i, word) in span.words.iter().enumerate() {
-                        let word_width = font_size * word.x_advance;
-                        if current_visual_line.w + (word_range_width + word_width)
+                        let word_width = word.width_at(
+                            font_size,
+                            current_visual_line.w + word_range_width,
+                            tab_stops,
+                            tab_width,
+                        );
+                        // Leave room for the hyphen in case the line is broken after this word
+                        let hyphen_width = word
+                            .hyphen_opt
+                            .as_ref()
+                            .map_or(0.0, |hyphen| hyphen.width(font_size));
+                        if current_visual_line.w + (word_range_width + word_width + hyphen_width)
                             <= line_width
                             // Include one blank word over the width limit since it won't be
                             // counted in the final width.
@@ -1057,9 +1303,17 @@ impl ShapeLine {
                             }
                             word_range_width += word_width;
                             continue;
-                        } else if wrap == Wrap::Glyph {
+                        } else if wrap == Wrap::Glyph
+                            // Fall back to glyph wrapping if the word does not fit on its own line
+                            || (wrap == Wrap::WordOrGlyph && word_width > line_width)
+                        {
                             for (glyph_i, glyph) in word.glyphs.iter().enumerate() {
-                                let glyph_width = font_size * glyph.x_advance;
+                                let glyph_width = glyph.width_at(
+                                    font_size,
+                                    current_visual_line.w + word_range_width,
+                                    tab_stops,
+                                    tab_width,
+                                );
                                 if current_visual_line.w + (word_range_width + glyph_width)
                                     <= line_width
                                 {
@@ -1078,7 +1332,9 @@ impl ShapeLine {
                                     current_visual_line = VisualLine::default();
 
                                     number_of_blanks = 0;
-                                    word_range_width = glyph_width;
+                                    // Tabs are measured again from the start of the new line
+                                    word_range_width =
+                                        glyph.width_at(font_size, 0.0, tab_stops, tab_width);
                                     fitting_start = (i, glyph_i);
                                 }
                             }
@@ -1116,7 +1372,9 @@ impl ShapeLine {
                                 word_range_width = 0.;
                                 fitting_start = (i + 1, 0);
                             } else {
-                                word_range_width = word_width;
+                                // Tabs are measured again from the start of the new line
+                                word_range_width =
+                                    word.width_at(font_size, 0.0, tab_stops, tab_width);
                                 fitting_start = (i, 0);
                             }
                         }
@@ -1137,6 +1395,20 @@ impl ShapeLine {
             visual_lines.push(current_visual_line);
         }
 
+        // Show the hyphen of words ending with a soft hyphen where the line is broken after them
+        let broken_lines = visual_lines.len().saturating_sub(1);
+        for visual_line in visual_lines.iter_mut().take(broken_lines) {
+            if let Some(&(span_index, _, (ending_word, 0))) = visual_line.ranges.last() {
+                let span = &self.spans[span_index];
+                if span.level.is_rtl() == self.rtl && ending_word > 0 {
+                    if let Some(hyphen) = &span.words[ending_word - 1].hyphen_opt {
+                        visual_line.w += hyphen.width(font_size);
+                        visual_line.hyphen_opt = Some((span_index, ending_word - 1));
+                    }
+                }
+            }
+        }
+
         // Create the LayoutLines using the ranges inside visual lines
         let align = align.unwrap_or({
             if self.rtl {
@@ -1160,6 +1432,7 @@ impl ShapeLine {
             let mut max_ascent: f32 = 0.;
             let mut max_descent: f32 = 0.;
             let alignment_correction = match (align, self.rtl) {
+                (Align::Start, _) => 0.,
                 (Align::Left, true) => line_width - visual_line.w,
                 (Align::Left, false) => 0.,
                 (Align::Right, true) => 0.,
@@ -1174,6 +1447,7 @@ impl ShapeLine {
             } else {
                 x += alignment_correction;
             }
+            let line_start = x;
 
             // TODO: Only certain `is_whitespace` chars are typically expanded but this is what is
             // currently used to compute `visual_line.spaces`.
@@ -1214,8 +1488,30 @@ impl ShapeLine {
                             (false, true) => &word.glyphs[..ending_glyph],
                             (true, true) => &word.glyphs[starting_glyph..ending_glyph],
                         };
-                        for glyph in included_glyphs {
-                            let x_advance = font_size * glyph.x_advance
+                        let hyphen_opt = if visual_line.hyphen_opt == Some((span_index, i)) {
+                            word.hyphen_opt.as_ref()
+                        } else {
+                            None
+                        };
+                        // The hyphen follows the last glyph in logical order, which comes first
+                        // in right-to-left lines
+                        let (hyphen_before, hyphen_after) = if self.rtl {
+                            (hyphen_opt, None)
+                        } else {
+                            (None, hyphen_opt)
+                        };
+                        for glyph in hyphen_before
+                            .into_iter()
+                            .chain(included_glyphs)
+                            .chain(hyphen_after)
+                        {
+                            let distance = if self.rtl {
+                                line_start - x
+                            } else {
+                                x - line_start
+                            };
+                            let x_advance = glyph
+                                .width_at(font_size, distance, tab_stops, tab_width)
                                 + if word.blank {
                                     justification_expansion
                                 } else {
@@ -10,16 +10,30 @@ use std::collections::HashMap as Map;
 use swash::scale::{image::Content, ScaleContext};
 use swash::scale::{Render, Source, StrikeWith};
 use swash::zeno::{Format, Vector};
+use swash::Setting;
 
 use crate::{CacheKey, Color, FontSystem};
 
 pub use swash::scale::image::{Content as SwashContent, Image as SwashImage};
 pub use swash::zeno::{Command, Placement};
 
+/// Get the variable font axes of `cache_key` as swash settings
+fn swash_variations(cache_key: &CacheKey) -> Vec<Setting<f32>> {
+    cache_key
+        .font_variations
+        .iter()
+        .flat_map(|font_variations| font_variations.iter())
+        .map(|(tag, value)| Setting {
+            tag: swash::tag_from_bytes(&tag.0),
+            value,
+        })
+        .collect()
+}
+
 fn swash_image(
     font_system: &mut FontSystem,
     context: &mut ScaleContext,
-    cache_key: CacheKey,
+    cache_key: &CacheKey,
 ) -> Option<SwashImage> {
     let font = match font_system.get_font(cache_key.font_id) {
         Some(some) => some,
@@ -34,6 +48,7 @@ fn swash_image(
         .builder(font.as_swash())
         .size(f32::from_bits(cache_key.font_size_bits))
         .hint(true)
+        .variations(swash_variations(cache_key))
         .build();
 
     // Compute the fractional offset-- you'll likely want to quantize this
@@ -60,7 +75,7 @@ fn swash_image(
 fn swash_outline_commands(
     font_system: &mut FontSystem,
     context: &mut ScaleContext,
-    cache_key: CacheKey,
+    cache_key: &CacheKey,
 ) -> Option<Vec<swash::zeno::Command>> {
     use swash::zeno::PathData as _;
 
@@ -76,6 +91,7 @@ fn swash_outline_commands(
     let mut scaler = context
         .builder(font.as_swash())
         .size(f32::from_bits(cache_key.font_size_bits))
+        .variations(swash_variations(cache_key))
         .build();
 
     // Scale the outline
@@ -119,7 +135,7 @@ impl SwashCache {
         font_system: &mut FontSystem,
         cache_key: CacheKey,
     ) -> Option<SwashImage> {
-        swash_image(font_system, &mut self.context, cache_key)
+        swash_image(font_system, &mut self.context, &cache_key)
     }
 
     /// Create a swash Image from a cache key, caching results
@@ -129,8 +145,8 @@ impl SwashCache {
         cache_key: CacheKey,
     ) -> &Option<SwashImage> {
         self.image_cache
-            .entry(cache_key)
-            .or_insert_with(|| swash_image(font_system, &mut self.context, cache_key))
+            .entry(cache_key.clone())
+            .or_insert_with(|| swash_image(font_system, &mut self.context, &cache_key))
     }
 
     pub fn get_outline_commands(
@@ -139,8 +155,8 @@ impl SwashCache {
         cache_key: CacheKey,
     ) -> Option<&[swash::zeno::Command]> {
         self.outline_command_cache
-            .entry(cache_key)
-            .or_insert_with(|| swash_outline_commands(font_system, &mut self.context, cache_key))
+            .entry(cache_key.clone())
+            .or_insert_with(|| swash_outline_commands(font_system, &mut self.context, &cache_key))
             .as_deref()
     }
 
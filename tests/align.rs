@@ -0,0 +1,78 @@
+mod common;
+
+use cosmic_text::{Align, Attrs, Buffer};
+
+const FONTS: &[&str] = &["NotoSans-Regular.ttf", "NotoSansHebrew.ttf"];
+
+/// Get the left and right edges of the glyphs of each layout line
+fn line_edges(buffer: &Buffer) -> Vec<(f32, f32)> {
+    buffer
+        .layout_runs()
+        .map(|run| {
+            let left = run.glyphs.iter().map(|g| g.x).fold(f32::MAX, f32::min);
+            let right = run.glyphs.iter().map(|g| g.x + g.w).fold(0.0, f32::max);
+            (left, right)
+        })
+        .collect()
+}
+
+#[test]
+fn align_start() {
+    let mut font_system = common::font_system(FONTS);
+    // Start is left in LTR lines and right in RTL lines
+    let mut buffer = common::buffer_with_text(&mut font_system, "hello\nשלום", Attrs::new(), 200.0);
+    buffer.set_align(&mut font_system, Some(Align::Start));
+    let edges = line_edges(&buffer);
+    assert!(edges[0].0.abs() < 0.5, "{edges:?}");
+    assert!((edges[1].1 - 200.0).abs() < 0.5, "{edges:?}");
+
+    // Lines without an alignment also start at the start edge
+    let buffer = common::buffer_with_text(&mut font_system, "hello\nשלום", Attrs::new(), 200.0);
+    assert_eq!(line_edges(&buffer), edges);
+
+    // Left is the same in both directions
+    let mut buffer = common::buffer_with_text(&mut font_system, "hello\nשלום", Attrs::new(), 200.0);
+    buffer.set_align(&mut font_system, Some(Align::Left));
+    let edges = line_edges(&buffer);
+    assert!(edges[0].0.abs() < 0.5, "{edges:?}");
+    assert!(edges[1].0.abs() < 0.5, "{edges:?}");
+}
+
+#[test]
+fn align_from_attrs() {
+    let mut font_system = common::font_system(FONTS);
+    // The alignment of the attributes at the start of a line overrides the buffer default
+    let mut buffer = common::buffer_with_text(
+        &mut font_system,
+        "hello",
+        Attrs::new().align(Align::Start),
+        200.0,
+    );
+    buffer.set_align(&mut font_system, Some(Align::End));
+    let edges = line_edges(&buffer);
+    assert!(edges[0].0.abs() < 0.5, "{edges:?}");
+
+    let mut buffer = common::buffer_with_text(&mut font_system, "hello", Attrs::new(), 200.0);
+    buffer.set_align(&mut font_system, Some(Align::End));
+    let edges = line_edges(&buffer);
+    assert!((edges[0].1 - 200.0).abs() < 0.5, "{edges:?}");
+}
+
+#[test]
+fn align_justified() {
+    let mut font_system = common::font_system(FONTS);
+    let text = "The quick brown fox jumps over the lazy dog, again and again and again.";
+    for line in [text.to_string(), format!("שלום {text}")] {
+        let mut buffer = common::buffer_with_text(&mut font_system, &line, Attrs::new(), 200.0);
+        buffer.set_align(&mut font_system, Some(Align::Justified));
+        let widths: Vec<f32> = buffer.layout_runs().map(|run| run.line_w).collect();
+        assert!(widths.len() > 2, "{widths:?}");
+
+        // All lines but the last fill the width
+        let (last, rest) = widths.split_last().unwrap();
+        for width in rest {
+            assert!((width - 200.0).abs() < 1.0, "{widths:?}");
+        }
+        assert!(*last < 199.0, "{widths:?}");
+    }
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use cosmic_text::{Attrs, Buffer, Metrics, Shaping};
+
+#[test]
+fn bidi_info_for_line() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf", "NotoSansHebrew.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "ab אב", Attrs::new(), Shaping::Advanced);
+
+    let bidi = buffer.bidi_info_for_line(0).unwrap();
+    assert_eq!(bidi.paragraph_level, 0);
+    assert_eq!(bidi.levels, [0, 0, 0, 1, 1]);
+    assert_eq!(bidi.visual_order, [0, 1, 2, 5, 3]);
+    assert_eq!(buffer.bidi_info_for_line(1), None);
+}
+
+#[test]
+fn bidi_info_for_line_with_paragraphs() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf", "NotoSansHebrew.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    // A paragraph separator starts a new bidi paragraph in the same line
+    buffer.set_text(
+        &mut font_system,
+        "ab\u{2029}אב",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+
+    let bidi = buffer.bidi_info_for_line(0).unwrap();
+    assert_eq!(bidi.paragraph_level, 0);
+    assert_eq!(bidi.levels, [0, 0, 0, 1, 1]);
+    // Each paragraph is reordered on its own, and the second one is not dropped
+    assert_eq!(bidi.visual_order, [0, 1, 2, 7, 5]);
+}
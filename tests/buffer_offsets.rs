@@ -0,0 +1,30 @@
+mod common;
+
+use cosmic_text::{Attrs, Buffer, Cursor, Metrics, Shaping};
+
+#[test]
+fn line_ranges() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "héllo\n\nworld",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+
+    assert_eq!(buffer.line_range(0), Some(0..6));
+    assert_eq!(buffer.line_range(1), Some(7..7));
+    assert_eq!(buffer.line_range(2), Some(8..13));
+    assert_eq!(buffer.line_range(3), None);
+
+    assert_eq!(
+        buffer.lines[2].byte_range_in_buffer(2, &buffer),
+        Some(8..13)
+    );
+    // Out of range lines return None instead of panicking
+    assert_eq!(buffer.lines[0].byte_range_in_buffer(5, &buffer), None);
+
+    assert_eq!(buffer.offset_to_cursor(8), Some(Cursor::new(2, 0)));
+    assert_eq!(buffer.offset_to_cursor(2), None);
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use cosmic_text::{Action, Attrs, AttrsList, Buffer, Cursor, Edit, Editor, Metrics, Shaping};
+
+#[test]
+fn undo_redo() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+
+    // Nothing is recorded before history is enabled
+    buffer.set_text(&mut font_system, "one", Attrs::new(), Shaping::Advanced);
+    buffer.enable_history(2);
+    assert!(!buffer.undo(&mut font_system));
+
+    buffer.set_text(&mut font_system, "two", Attrs::new(), Shaping::Advanced);
+    buffer.set_text(
+        &mut font_system,
+        "three\nlines",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    assert!(buffer.undo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "two");
+    assert!(buffer.undo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "one");
+    assert!(!buffer.undo(&mut font_system));
+
+    assert!(buffer.redo(&mut font_system));
+    assert!(buffer.redo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "three\nlines");
+    assert!(!buffer.redo(&mut font_system));
+
+    // A new change discards the redo steps
+    buffer.undo(&mut font_system);
+    buffer.set_text(&mut font_system, "four", Attrs::new(), Shaping::Advanced);
+    assert!(!buffer.redo(&mut font_system));
+
+    // Only `max_depth` undo steps are kept
+    buffer.set_text(&mut font_system, "five", Attrs::new(), Shaping::Advanced);
+    assert!(buffer.undo(&mut font_system));
+    assert!(buffer.undo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "two");
+    assert!(!buffer.undo(&mut font_system));
+
+    // Disabling history discards all steps
+    buffer.disable_history();
+    assert!(!buffer.redo(&mut font_system));
+}
+
+#[test]
+fn record_direct_edits() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "hello", Attrs::new(), Shaping::Advanced);
+    buffer.enable_history(10);
+
+    buffer.record_history();
+    buffer.lines[0].set_text("world", AttrsList::new(Attrs::new()));
+    assert!(buffer.undo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "hello");
+    assert!(buffer.redo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "world");
+}
+
+#[test]
+fn undo_replace_range() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "hello\nworld",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.enable_history(10);
+
+    let end = buffer.replace_range(Cursor::new(0, 2), Cursor::new(1, 3), "y\nno", None);
+    assert_eq!(end, Cursor::new(1, 2));
+    assert_eq!(common::buffer_text(&buffer), "hey\nnold");
+
+    assert!(buffer.undo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "hello\nworld");
+    assert!(buffer.redo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "hey\nnold");
+}
+
+#[test]
+fn undo_append_text() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(&mut font_system, "one", Attrs::new(), Shaping::Advanced);
+    buffer.enable_history(10);
+
+    // Each append is one undo step
+    buffer.append_text(
+        &mut font_system,
+        " two\nthree",
+        AttrsList::new(Attrs::new()),
+    );
+    buffer.append_text(&mut font_system, "\nfour", AttrsList::new(Attrs::new()));
+    assert_eq!(common::buffer_text(&buffer), "one two\nthree\nfour");
+
+    assert!(buffer.undo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "one two\nthree");
+    assert!(buffer.undo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "one");
+    assert!(!buffer.undo(&mut font_system));
+    assert!(buffer.redo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "one two\nthree");
+}
+
+#[test]
+fn undo_keeps_other_lines() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "one\ntwo\nthree",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.enable_history(10);
+
+    buffer.replace_range(Cursor::new(0, 0), Cursor::new(0, 3), "first", None);
+    buffer.replace_range(Cursor::new(2, 5), Cursor::new(2, 5), "\nfour", None);
+    assert_eq!(common::buffer_text(&buffer), "first\ntwo\nthree\nfour");
+
+    // Undo only puts back the lines replaced by each edit
+    assert!(buffer.undo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "first\ntwo\nthree");
+    assert!(buffer.undo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "one\ntwo\nthree");
+    assert!(buffer.redo(&mut font_system));
+    assert!(buffer.redo(&mut font_system));
+    assert_eq!(common::buffer_text(&buffer), "first\ntwo\nthree\nfour");
+}
+
+#[test]
+fn editor_edits_not_recorded() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "hello world",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.enable_history(10);
+    let mut editor = Editor::new(buffer);
+
+    editor.set_cursor(Cursor::new(0, 5));
+    editor.set_select_opt(Some(Cursor::new(0, 0)));
+    editor.action(&mut font_system, Action::Backspace);
+    assert_eq!(common::text(&editor), " world");
+
+    // The editor keeps its own history
+    assert!(!editor.buffer_mut().undo(&mut font_system));
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "hello world");
+}
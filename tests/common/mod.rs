@@ -1,11 +1,62 @@
+#![allow(dead_code)]
+
 use std::path::PathBuf;
 
 use cosmic_text::{
-    fontdb::Database, Attrs, AttrsOwned, Buffer, Color, Family, FontSystem, Metrics, Shaping,
-    SwashCache,
+    fontdb::Database, Attrs, AttrsOwned, Buffer, Color, Editor, Family, FontSystem, Metrics,
+    Shaping, SwashCache,
 };
 use tiny_skia::{Paint, Pixmap, Rect, Transform};
 
+/// Create a [`FontSystem`] with only the given fonts from the `fonts` directory in this repository
+pub fn font_system(fonts: &[&str]) -> FontSystem {
+    let repo_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let fonts_path = PathBuf::from(&repo_dir).join("fonts");
+    let mut font_system = FontSystem::new_with_locale_and_db("en-US".into(), Database::new());
+    for font in fonts {
+        font_system
+            .db_mut()
+            .load_font_data(std::fs::read(fonts_path.join(font)).unwrap());
+    }
+    font_system
+}
+
+/// Create an [`Editor`] with `text` in a buffer with the default attributes
+pub fn editor_with_text(font_system: &mut FontSystem, text: &str) -> Editor {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(font_system, text, Attrs::new(), Shaping::Advanced);
+    Editor::new(buffer)
+}
+
+/// Create a [`Buffer`] with `text` in the given attributes, shaped and laid out at `width`
+pub fn buffer_with_text(
+    font_system: &mut FontSystem,
+    text: &str,
+    attrs: Attrs,
+    width: f32,
+) -> Buffer {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(font_system, width, 1000.0);
+    buffer.set_text(font_system, text, attrs, Shaping::Advanced);
+    buffer.shape_until_scroll(font_system);
+    buffer
+}
+
+/// Get the text of the buffer of an [`Editor`], with lines joined by newlines
+pub fn text(editor: &Editor) -> String {
+    buffer_text(editor.buffer())
+}
+
+/// Get the text of a [`Buffer`], with lines joined by newlines
+pub fn buffer_text(buffer: &Buffer) -> String {
+    buffer
+        .lines
+        .iter()
+        .map(|line| line.text())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// The test configuration.
 /// The text in the test will be rendered as image using the one of the fonts found under the
 /// `fonts` directory in this repository.
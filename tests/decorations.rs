@@ -0,0 +1,51 @@
+#![cfg(feature = "swash")]
+
+mod common;
+
+use cosmic_text::{Attrs, Buffer, Color, Family, Metrics, Shaping, SwashCache, TextDecoration};
+
+#[test]
+fn decoration_positions() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut swash_cache = SwashCache::new();
+
+    let decoration_color = Color::rgb(0xFF, 0x00, 0x00);
+    let attrs = Attrs::new()
+        .family(Family::Name("Noto Sans"))
+        .text_decoration(TextDecoration::UNDERLINE | TextDecoration::STRIKETHROUGH)
+        .decoration_color(decoration_color);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(20.0, 30.0));
+    buffer.set_size(&mut font_system, 500.0, 100.0);
+    buffer.set_text(&mut font_system, "Hello", attrs, Shaping::Advanced);
+
+    let mut rects = Vec::new();
+    buffer.draw(
+        &mut font_system,
+        &mut swash_cache,
+        Color::rgb(0x00, 0x00, 0x00),
+        |x, y, w, h, color| {
+            if color == decoration_color {
+                rects.push((x, y, w, h));
+            }
+        },
+    );
+
+    let run = buffer.layout_runs().next().unwrap();
+    let glyph = &run.glyphs[0];
+    let font = font_system.get_font(glyph.font_id).unwrap();
+    let face = font.rustybuzz();
+    let scale = glyph.font_size / face.units_per_em() as f32;
+    let underline = face.underline_metrics().unwrap();
+    let strikeout = face.strikeout_metrics().unwrap();
+
+    // One rectangle for each decoration, covering the whole word
+    let ys: Vec<i32> = rects.iter().map(|rect| rect.1).collect();
+    assert_eq!(
+        ys,
+        [
+            (run.line_y - underline.position as f32 * scale) as i32,
+            (run.line_y - strikeout.position as f32 * scale) as i32,
+        ]
+    );
+    assert!(rects.iter().all(|rect| rect.0 == 0 && rect.2 > 0));
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use cosmic_text::{Attrs, Buffer, DocumentLayout, Metrics, Shaping};
+
+fn block(font_system: &mut cosmic_text::FontSystem, text: &str, height: f32) -> Buffer {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(font_system, 500.0, height);
+    buffer.set_text(font_system, text, Attrs::new(), Shaping::Advanced);
+    buffer
+}
+
+#[test]
+fn document_layout() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let blocks = vec![
+        block(&mut font_system, "one\ntwo\nthree", 30.0),
+        block(&mut font_system, "four", 50.0),
+        block(&mut font_system, "five\nsix", 40.0),
+    ];
+    let mut document = DocumentLayout::new(blocks);
+
+    // Blocks keep the heights and scroll set by the caller
+    document.blocks[0].set_scroll(1);
+    document.update(&mut font_system);
+    assert_eq!(document.block_offset(1), Some(30.0));
+    assert_eq!(document.block_offset(2), Some(80.0));
+    assert_eq!(document.total_height(), 120.0);
+    assert_eq!(document.blocks[0].scroll(), 1);
+    assert_eq!(document.blocks[0].size().1, 30.0);
+
+    // Only blocks in the viewport are visible
+    assert_eq!(document.visible_blocks(0.0, 30.0), 0..1);
+    assert_eq!(document.visible_blocks(20.0, 20.0), 0..2);
+    assert_eq!(document.visible_blocks(85.0, 100.0), 2..3);
+    assert_eq!(document.visible_blocks(200.0, 100.0), 3..3);
+
+    let (block_i, cursor) = document.hit(10.0, 90.0).unwrap();
+    assert_eq!((block_i, cursor.line), (2, 0));
+
+    // Fitting the blocks shows all of their lines
+    document.fit_blocks_to_contents(&mut font_system);
+    assert_eq!(document.blocks[0].scroll(), 0);
+    assert_eq!(document.block_offset(1), Some(60.0));
+    assert_eq!(document.block_offset(2), Some(80.0));
+    assert_eq!(document.total_height(), 120.0);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use cosmic_text::{Action, Attrs, Buffer, Cursor, Edit, Editor, Family, Metrics, Shaping};
+
+#[test]
+fn column_selection() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    buffer.set_text(
+        &mut font_system,
+        "abcd\nefgh\nij",
+        Attrs::new().family(Family::Name("Fira Mono")),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+
+    editor.action(&mut font_system, Action::ToggleColumnSelection);
+    assert!(editor.column_selection());
+    editor.set_select_opt(Some(Cursor::new(0, 1)));
+    editor.set_cursor(Cursor::new(2, 2));
+    assert_eq!(editor.copy_selection().as_deref(), Some("b\nf\nj"));
+
+    // Typing replaces each row and leaves a cursor on each row
+    editor.action(&mut font_system, Action::Insert('X'));
+    editor.action(&mut font_system, Action::Insert('Y'));
+    assert_eq!(common::text(&editor), "aXYcd\neXYgh\niXY");
+    let cursors: Vec<(usize, usize)> = editor
+        .cursors()
+        .iter()
+        .map(|cursor| (cursor.line, cursor.index))
+        .collect();
+    assert_eq!(cursors, [(0, 3), (1, 3), (2, 3)]);
+
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "abcd\nefgh\nij");
+
+    // Without column selection, the same range selects the text in between
+    editor.action(&mut font_system, Action::ToggleColumnSelection);
+    assert!(!editor.column_selection());
+    editor.set_select_opt(Some(Cursor::new(0, 1)));
+    editor.set_cursor(Cursor::new(2, 2));
+    assert_eq!(editor.copy_selection().as_deref(), Some("bcd\nefgh\nij"));
+}
+
+#[test]
+fn column_selection_replace_range() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+    let mut editor = common::editor_with_text(&mut font_system, "abcd\nefgh\nij");
+    editor.action(&mut font_system, Action::ToggleColumnSelection);
+    editor.set_select_opt(Some(Cursor::new(0, 1)));
+    editor.set_cursor(Cursor::new(2, 2));
+    editor.add_cursor(Cursor::new(2, 0));
+
+    // Only the given range is replaced, not every row of the column selection
+    editor.replace_range(Cursor::new(1, 1), Cursor::new(1, 3), "XYZ", None);
+    assert_eq!(common::text(&editor), "abcd\neXYZh\nij");
+    assert_eq!(editor.cursor(), Cursor::new(1, 4));
+    assert_eq!(editor.select_opt(), None);
+    let cursors: Vec<(usize, usize)> = editor
+        .cursors()
+        .iter()
+        .map(|cursor| (cursor.line, cursor.index))
+        .collect();
+    assert_eq!(cursors, [(1, 4), (2, 0)]);
+
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "abcd\nefgh\nij");
+}
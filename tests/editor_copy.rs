@@ -0,0 +1,64 @@
+mod common;
+
+use cosmic_text::{Action, Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Metrics, Shaping};
+
+const FONTS: &[&str] = &[
+    "NotoSans-Regular.ttf",
+    "NotoSansHebrew.ttf",
+    "NotoSansArabic.ttf",
+];
+
+fn new_editor(font_system: &mut FontSystem, text: &str) -> Editor {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(font_system, 500.0, 500.0);
+    buffer.set_text(font_system, text, Attrs::new(), Shaping::Advanced);
+    Editor::new(buffer)
+}
+
+#[test]
+fn copy_rtl_in_logical_order() {
+    let mut font_system = common::font_system(FONTS);
+    let source = "שלום עולם hello\nمرحبا بالعالم 123";
+    let mut editor = new_editor(&mut font_system, source);
+    editor.shape_as_needed(&mut font_system);
+
+    // Select from the second Hebrew word to the middle of the Arabic line with visual movement
+    let start = source.find("עולם").unwrap();
+    editor.set_cursor(Cursor::new(0, start));
+    editor.action(&mut font_system, Action::DownExtend);
+    editor.action(&mut font_system, Action::NextWordExtend);
+    let cursor = editor.cursor();
+    assert_eq!(cursor.line, 1);
+
+    let second_line = source.split('\n').nth(1).unwrap();
+    let expected = format!(
+        "{}\n{}",
+        &source[start..].split('\n').next().unwrap(),
+        &second_line[..cursor.index]
+    );
+    let copied = editor.copy_selection().unwrap();
+    assert_eq!(copied, expected);
+
+    // Pasting the copied text reproduces it
+    let mut paste = new_editor(&mut font_system, "");
+    paste.insert_string(&copied, None);
+    assert_eq!(common::text(&paste), copied);
+}
+
+#[test]
+fn copy_mixed_direction_line() {
+    let mut font_system = common::font_system(FONTS);
+    let source = "abc שלום def مرحبا ghi";
+    let mut editor = new_editor(&mut font_system, source);
+    editor.shape_as_needed(&mut font_system);
+
+    editor.set_cursor(Cursor::new(0, 0));
+    editor.action(&mut font_system, Action::SelectAll);
+    assert_eq!(editor.copy_selection().as_deref(), Some(source));
+
+    // A selection within the right-to-left word is copied in logical order
+    let start = source.find("שלום").unwrap();
+    editor.set_select_opt(Some(Cursor::new(0, start)));
+    editor.set_cursor(Cursor::new(0, start + "שלום".len()));
+    assert_eq!(editor.copy_selection().as_deref(), Some("שלום"));
+}
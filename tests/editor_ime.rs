@@ -0,0 +1,39 @@
+mod common;
+
+use cosmic_text::{Action, Cursor, Edit};
+
+#[test]
+fn ime_preedit_and_commit() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut editor = common::editor_with_text(&mut font_system, "ab");
+    editor.set_cursor(Cursor::new(0, 1));
+
+    editor.set_ime_preedit("ka", None);
+    assert_eq!(common::text(&editor), "akab");
+    assert_eq!(editor.ime_preedit().as_deref(), Some("ka"));
+    assert_eq!(editor.cursor().index, 3);
+
+    // Preedit text is not copied
+    editor.set_select_opt(Some(Cursor::new(0, 0)));
+    editor.set_cursor(Cursor::new(0, 4));
+    assert_eq!(editor.copy_selection().as_deref(), Some("ab"));
+    editor.set_select_opt(None);
+
+    // Updating the preedit text replaces it, with the cursor in the given range
+    editor.set_ime_preedit("kan", Some((1, 1)));
+    assert_eq!(common::text(&editor), "akanb");
+    assert_eq!(editor.cursor().index, 2);
+
+    editor.commit_ime("\u{304b}\u{3093}");
+    assert_eq!(common::text(&editor), "a\u{304b}\u{3093}b");
+    assert_eq!(editor.ime_preedit(), None);
+
+    // Only the committed text is undone
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "ab");
+
+    // Any action removes the preedit text
+    editor.set_ime_preedit("ka", None);
+    editor.action(&mut font_system, Action::Escape);
+    assert_eq!(common::text(&editor), "ab");
+}
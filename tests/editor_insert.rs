@@ -0,0 +1,25 @@
+mod common;
+
+use cosmic_text::{Cursor, Edit};
+
+#[test]
+fn insert_string_at_keeps_cursors() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut editor = common::editor_with_text(&mut font_system, "hello world\nsecond");
+    editor.set_select_opt(Some(Cursor::new(0, 6)));
+    editor.set_cursor(Cursor::new(0, 11));
+
+    // Inserting before the selection moves it
+    let end = editor.insert_string_at(Cursor::new(0, 0), "a\nb ", None);
+    assert_eq!(end, Cursor::new(1, 2));
+    assert_eq!(common::text(&editor), "a\nb hello world\nsecond");
+    assert_eq!(editor.select_opt(), Some(Cursor::new(1, 8)));
+    assert_eq!(editor.cursor(), Cursor::new(1, 13));
+
+    // Inserting after the selection leaves it in place
+    let end = editor.insert_string_at(Cursor::new(2, 6), "!", None);
+    assert_eq!(end, Cursor::new(2, 7));
+    assert_eq!(common::text(&editor), "a\nb hello world\nsecond!");
+    assert_eq!(editor.select_opt(), Some(Cursor::new(1, 8)));
+    assert_eq!(editor.cursor(), Cursor::new(1, 13));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use cosmic_text::{Action, Cursor, Edit, Editor};
+
+fn cursors(editor: &Editor) -> Vec<(usize, usize)> {
+    editor
+        .cursors()
+        .iter()
+        .map(|cursor| (cursor.line, cursor.index))
+        .collect()
+}
+
+#[test]
+fn multi_cursor_insert() {
+    let mut font_system = common::font_system(&[]);
+    let mut editor = common::editor_with_text(&mut font_system, "one\ntwo\nthree");
+    assert_eq!(cursors(&editor), [(0, 0)]);
+
+    editor.add_cursor(Cursor::new(2, 0));
+    editor.add_cursor(Cursor::new(1, 0));
+    assert_eq!(cursors(&editor), [(0, 0), (1, 0), (2, 0)]);
+
+    editor.action(&mut font_system, Action::Insert('-'));
+    editor.action(&mut font_system, Action::Insert(' '));
+    assert_eq!(common::text(&editor), "- one\n- two\n- three");
+    assert_eq!(cursors(&editor), [(0, 2), (1, 2), (2, 2)]);
+
+    // All cursors are moved independently
+    editor.action(&mut font_system, Action::End);
+    assert_eq!(cursors(&editor), [(0, 5), (1, 5), (2, 7)]);
+
+    // Escape returns to the main cursor
+    editor.action(&mut font_system, Action::Escape);
+    assert_eq!(cursors(&editor), [(0, 5)]);
+
+    // Changes from all cursors are undone together
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "one\ntwo\nthree");
+}
+
+#[test]
+fn multi_cursor_enter() {
+    let mut font_system = common::font_system(&[]);
+    let mut editor = common::editor_with_text(&mut font_system, "ab\ncd");
+    editor.set_cursor(Cursor::new(0, 1));
+    editor.add_cursor(Cursor::new(1, 1));
+
+    editor.action(&mut font_system, Action::Enter);
+    assert_eq!(common::text(&editor), "a\nb\nc\nd");
+    assert_eq!(cursors(&editor), [(1, 0), (3, 0)]);
+    assert_eq!(
+        (editor.cursor().line, editor.cursor().index),
+        (1, 0),
+        "main cursor should stay the main cursor"
+    );
+}
+
+#[test]
+fn multi_cursor_merge() {
+    let mut font_system = common::font_system(&[]);
+    let mut editor = common::editor_with_text(&mut font_system, "ab");
+    editor.set_cursor(Cursor::new(0, 1));
+    editor.add_cursor(Cursor::new(0, 2));
+
+    editor.action(&mut font_system, Action::Backspace);
+    assert_eq!(common::text(&editor), "");
+    assert_eq!(cursors(&editor), [(0, 0)]);
+}
+
+#[test]
+fn multi_cursor_insert_string_at() {
+    let mut font_system = common::font_system(&[]);
+    let mut editor = common::editor_with_text(&mut font_system, "one\ntwo\nthree");
+    editor.set_cursor(Cursor::new(0, 3));
+    editor.add_cursor(Cursor::new(1, 1));
+    editor.add_cursor(Cursor::new(2, 2));
+
+    // Cursors at or after the insertion move, others stay
+    let end = editor.insert_string_at(Cursor::new(1, 0), "new\n", None);
+    assert_eq!(end, Cursor::new(2, 0));
+    assert_eq!(common::text(&editor), "one\nnew\ntwo\nthree");
+    assert_eq!(cursors(&editor), [(0, 3), (2, 1), (3, 2)]);
+    assert_eq!(editor.cursor(), Cursor::new(0, 3));
+
+    let end = editor.insert_string_at(Cursor::new(2, 1), "-", None);
+    assert_eq!(end, Cursor::new(2, 2));
+    assert_eq!(common::text(&editor), "one\nnew\nt-wo\nthree");
+    assert_eq!(cursors(&editor), [(0, 3), (2, 2), (3, 2)]);
+
+    // Typing continues at every cursor
+    editor.action(&mut font_system, Action::Insert('!'));
+    assert_eq!(common::text(&editor), "one!\nnew\nt-!wo\nth!ree");
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use cosmic_text::{
+    Action, Attrs, Buffer, ClickHandler, Cursor, DefaultClickHandler, Edit, Editor, Metrics,
+    Shaping,
+};
+
+const FONTS: &[&str] = &[
+    "NotoSans-Regular.ttf",
+    "NotoSansArabic.ttf",
+    "NotoSansHebrew.ttf",
+];
+
+fn selection(editor: &Editor) -> Option<((usize, usize), (usize, usize))> {
+    let select = editor.select_opt()?;
+    let cursor = editor.cursor();
+    Some(((select.line, select.index), (cursor.line, cursor.index)))
+}
+
+#[test]
+fn select_line() {
+    let mut font_system = common::font_system(FONTS);
+    let first = "héllo wörld  ";
+    let second = "שלום עולם ";
+    let text = format!("{first}\n{second}\nlast");
+    let mut editor = common::editor_with_text(&mut font_system, &text);
+
+    editor.set_cursor(Cursor::new(0, 3));
+    editor.action(&mut font_system, Action::SelectLine);
+    assert_eq!(selection(&editor), Some(((0, 0), (0, first.len()))));
+
+    // Selecting the line again does not change the selection
+    editor.action(&mut font_system, Action::SelectLine);
+    assert_eq!(selection(&editor), Some(((0, 0), (0, first.len()))));
+
+    editor.set_select_opt(None);
+    editor.set_cursor(Cursor::new(1, "שלום".len()));
+    editor.action(&mut font_system, Action::SelectLine);
+    assert_eq!(selection(&editor), Some(((1, 0), (1, second.len()))));
+}
+
+#[test]
+fn select_all() {
+    let mut font_system = common::font_system(FONTS);
+
+    let mut editor = common::editor_with_text(&mut font_system, "");
+    editor.action(&mut font_system, Action::SelectAll);
+    assert_eq!(selection(&editor), Some(((0, 0), (0, 0))));
+
+    let mut editor = common::editor_with_text(&mut font_system, "héllo wörld");
+    editor.set_cursor(Cursor::new(0, 3));
+    editor.action(&mut font_system, Action::SelectAll);
+    assert_eq!(selection(&editor), Some(((0, 0), (0, "héllo wörld".len()))));
+
+    let last = "مرحبا بالعالم";
+    let text = format!("first\nשלום עולם\n{last}");
+    let mut editor = common::editor_with_text(&mut font_system, &text);
+    editor.set_cursor(Cursor::new(1, 2));
+    editor.action(&mut font_system, Action::SelectAll);
+    assert_eq!(selection(&editor), Some(((0, 0), (2, last.len()))));
+    assert_eq!(editor.copy_selection().as_deref(), Some(text.as_str()));
+}
+
+#[test]
+fn extend_selection() {
+    let mut font_system = common::font_system(FONTS);
+    let mut editor = common::editor_with_text(&mut font_system, "hello world\nsecond");
+
+    editor.set_cursor(Cursor::new(0, 6));
+    editor.action(&mut font_system, Action::NextExtend);
+    assert_eq!(selection(&editor), Some(((0, 6), (0, 7))));
+
+    // The anchor is kept when extending further
+    editor.action(&mut font_system, Action::NextWordExtend);
+    assert_eq!(selection(&editor), Some(((0, 6), (0, 11))));
+
+    editor.action(&mut font_system, Action::BufferEndExtend);
+    assert_eq!(selection(&editor), Some(((0, 6), (1, 6))));
+}
+
+#[test]
+fn smart_home() {
+    let mut font_system = common::font_system(FONTS);
+    let mut editor = common::editor_with_text(&mut font_system, "    indented");
+    editor.set_cursor(Cursor::new(0, 8));
+
+    editor.action(&mut font_system, Action::SmartHome);
+    assert_eq!(editor.cursor().index, 4);
+    editor.action(&mut font_system, Action::SmartHome);
+    assert_eq!(editor.cursor().index, 0);
+    editor.action(&mut font_system, Action::SmartHome);
+    assert_eq!(editor.cursor().index, 4);
+}
+
+#[test]
+fn paragraph_navigation() {
+    let mut font_system = common::font_system(FONTS);
+    let mut editor = common::editor_with_text(&mut font_system, "one\ntwo\n\nthree\n\n\nfour");
+    editor.set_cursor(Cursor::new(1, 2));
+
+    editor.action(&mut font_system, Action::NextParagraph);
+    assert_eq!((editor.cursor().line, editor.cursor().index), (3, 0));
+    editor.action(&mut font_system, Action::NextParagraph);
+    assert_eq!((editor.cursor().line, editor.cursor().index), (6, 0));
+    editor.action(&mut font_system, Action::NextParagraph);
+    assert_eq!((editor.cursor().line, editor.cursor().index), (6, 4));
+
+    // The start of the current paragraph is found first if the cursor is after it
+    editor.action(&mut font_system, Action::PreviousParagraph);
+    assert_eq!((editor.cursor().line, editor.cursor().index), (6, 0));
+    editor.action(&mut font_system, Action::PreviousParagraph);
+    assert_eq!((editor.cursor().line, editor.cursor().index), (3, 0));
+    editor.action(&mut font_system, Action::PreviousParagraph);
+    assert_eq!((editor.cursor().line, editor.cursor().index), (0, 0));
+}
+
+#[test]
+fn multi_click() {
+    let mut font_system = common::font_system(FONTS);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    buffer.set_text(
+        &mut font_system,
+        "hello world\nsecond",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    editor.shape_as_needed(&mut font_system);
+
+    // Clicks at the same position are not counted by the editor
+    editor.action(&mut font_system, Action::Click { x: 2, y: 10 });
+    editor.action(&mut font_system, Action::Click { x: 2, y: 10 });
+    assert_eq!(selection(&editor), None);
+
+    let click = |count| Action::MultiClick { x: 2, y: 10, count };
+    editor.action(&mut font_system, click(2));
+    assert_eq!(selection(&editor), Some(((0, 0), (0, "hello".len()))));
+    editor.action(&mut font_system, click(3));
+    assert_eq!(selection(&editor), Some(((0, 0), (0, "hello world".len()))));
+    editor.action(&mut font_system, click(1));
+    assert_eq!(selection(&editor), None);
+
+    // Counts above 3 cycle back to a single click
+    editor.action(&mut font_system, click(5));
+    assert_eq!(selection(&editor), Some(((0, 0), (0, "hello".len()))));
+
+    // A count of 0 is a single click
+    editor.action(&mut font_system, click(0));
+    assert_eq!(selection(&editor), None);
+    editor.action(&mut font_system, click(2));
+    DefaultClickHandler.on_click(&mut editor, 2, 10, 0);
+    assert_eq!(selection(&editor), None);
+    DefaultClickHandler.on_click(&mut editor, 2, 10, 6);
+    assert_eq!(selection(&editor), Some(((0, 0), (0, "hello world".len()))));
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use cosmic_text::{
+    Action, Attrs, Buffer, Cursor, DragData, Edit, Editor, Family, Metrics, Shaping,
+};
+
+#[test]
+fn undo_redo() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut editor = common::editor_with_text(&mut font_system, "hello");
+    editor.set_cursor(Cursor::new(0, 5));
+
+    // Consecutive inserts are undone together
+    for c in " world".chars() {
+        editor.action(&mut font_system, Action::Insert(c));
+    }
+    editor.action(&mut font_system, Action::Enter);
+    editor.action(&mut font_system, Action::Backspace);
+    assert_eq!(common::text(&editor), "hello world");
+
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "hello world\n");
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "hello world");
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "hello");
+    assert_eq!(editor.cursor().index, 5);
+
+    editor.action(&mut font_system, Action::Redo);
+    assert_eq!(common::text(&editor), "hello world");
+    assert_eq!(editor.cursor().index, 11);
+
+    // A new change clears the redo stack
+    editor.action(&mut font_system, Action::Insert('!'));
+    editor.action(&mut font_system, Action::Redo);
+    assert_eq!(common::text(&editor), "hello world!");
+}
+
+#[test]
+fn line_duplicate_and_delete() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut editor = common::editor_with_text(&mut font_system, "one\ntwo");
+    editor.set_cursor(Cursor::new(0, 2));
+
+    editor.action(&mut font_system, Action::LineDuplicate);
+    assert_eq!(common::text(&editor), "one\none\ntwo");
+    assert_eq!(editor.cursor(), Cursor::new(1, 2));
+
+    editor.action(&mut font_system, Action::LineDelete);
+    assert_eq!(common::text(&editor), "one\ntwo");
+    assert_eq!(editor.cursor(), Cursor::new(1, 0));
+
+    // Deleting the last line moves the cursor to the end of the previous line
+    editor.action(&mut font_system, Action::LineDelete);
+    assert_eq!(common::text(&editor), "one");
+    assert_eq!(editor.cursor(), Cursor::new(0, 3));
+
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "one\ntwo");
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "one\none\ntwo");
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "one\ntwo");
+}
+
+#[test]
+fn move_line_up_and_down() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut editor = common::editor_with_text(&mut font_system, "one\ntwo\nthree");
+    editor.set_cursor(Cursor::new(2, 1));
+
+    editor.action(&mut font_system, Action::MoveLineUp);
+    assert_eq!(common::text(&editor), "one\nthree\ntwo");
+    assert_eq!(editor.cursor(), Cursor::new(1, 1));
+
+    editor.action(&mut font_system, Action::MoveLineUp);
+    editor.action(&mut font_system, Action::MoveLineUp);
+    assert_eq!(common::text(&editor), "three\none\ntwo");
+    assert_eq!(editor.cursor(), Cursor::new(0, 1));
+
+    editor.action(&mut font_system, Action::MoveLineDown);
+    assert_eq!(common::text(&editor), "one\nthree\ntwo");
+    assert_eq!(editor.cursor(), Cursor::new(1, 1));
+
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "three\none\ntwo");
+    assert_eq!(editor.cursor(), Cursor::new(0, 1));
+    editor.action(&mut font_system, Action::Redo);
+    assert_eq!(common::text(&editor), "one\nthree\ntwo");
+}
+
+#[test]
+fn indent_and_unindent() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut editor = common::editor_with_text(&mut font_system, "one\n  two\nthree");
+    editor.set_indent("    ");
+
+    // The last line is not indented if the selection ends at its start
+    editor.set_select_opt(Some(Cursor::new(0, 1)));
+    editor.set_cursor(Cursor::new(2, 0));
+    editor.action(&mut font_system, Action::Indent);
+    assert_eq!(common::text(&editor), "    one\n      two\nthree");
+    assert_eq!(editor.select_opt(), Some(Cursor::new(0, 5)));
+    assert_eq!(editor.cursor(), Cursor::new(2, 0));
+
+    editor.action(&mut font_system, Action::Unindent);
+    editor.action(&mut font_system, Action::Unindent);
+    assert_eq!(common::text(&editor), "one\ntwo\nthree");
+    assert_eq!(editor.select_opt(), Some(Cursor::new(0, 1)));
+
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "one\n  two\nthree");
+
+    // Without a selection spanning lines, the indent is inserted at the cursor
+    editor.set_select_opt(None);
+    editor.set_cursor(Cursor::new(2, 2));
+    editor.action(&mut font_system, Action::Indent);
+    assert_eq!(common::text(&editor), "one\n  two\nth    ree");
+    editor.set_cursor(Cursor::new(1, 5));
+    editor.action(&mut font_system, Action::Unindent);
+    assert_eq!(common::text(&editor), "one\ntwo\nth    ree");
+    assert_eq!(editor.cursor(), Cursor::new(1, 3));
+}
+
+fn column_editor(font_system: &mut cosmic_text::FontSystem, text: &str) -> Editor {
+    let mut buffer = Buffer::new(font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(font_system, 1000.0, 1000.0);
+    buffer.set_text(
+        font_system,
+        text,
+        Attrs::new().family(Family::Name("Fira Mono")),
+        Shaping::Advanced,
+    );
+    let mut editor = Editor::new(buffer);
+    editor.action(font_system, Action::ToggleColumnSelection);
+    assert!(editor.column_selection());
+    editor
+}
+
+#[test]
+fn undo_redo_in_column_mode() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+    let mut editor = column_editor(&mut font_system, "abcd\nefgh\nijkl");
+
+    // Multi-line changes are undone as ranges of text, not as rectangles
+    editor.set_cursor(Cursor::new(0, 2));
+    editor.insert_string("X\nY", None);
+    assert_eq!(common::text(&editor), "abX\nYcd\nefgh\nijkl");
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "abcd\nefgh\nijkl");
+    editor.action(&mut font_system, Action::Redo);
+    assert_eq!(common::text(&editor), "abX\nYcd\nefgh\nijkl");
+    editor.action(&mut font_system, Action::Undo);
+
+    editor.action(&mut font_system, Action::ToggleColumnSelection);
+    editor.set_select_opt(Some(Cursor::new(0, 1)));
+    editor.set_cursor(Cursor::new(2, 1));
+    editor.action(&mut font_system, Action::Delete);
+    assert_eq!(common::text(&editor), "ajkl");
+    editor.action(&mut font_system, Action::ToggleColumnSelection);
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "abcd\nefgh\nijkl");
+    editor.action(&mut font_system, Action::Redo);
+    assert_eq!(common::text(&editor), "ajkl");
+}
+
+#[test]
+fn drop_in_column_mode() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+    let mut editor = column_editor(&mut font_system, "abcd\nefgh\nijkl");
+    editor.shape_as_needed(&mut font_system);
+
+    // Moving the dragged text deletes the source range, not a rectangle
+    let data = DragData {
+        text: "bcd\nef".to_string(),
+        source_range: (Cursor::new(0, 1), Cursor::new(1, 2)),
+    };
+    editor.finish_drop(data, 900, 50, true);
+    assert_eq!(common::text(&editor), "agh\nijklbcd\nef");
+
+    editor.action(&mut font_system, Action::Undo);
+    assert_eq!(common::text(&editor), "abcd\nefgh\nijkl");
+}
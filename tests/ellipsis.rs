@@ -0,0 +1,43 @@
+mod common;
+
+use cosmic_text::{Attrs, Buffer, Family, Metrics, Overflow, Shaping, Wrap};
+
+#[test]
+fn ellipsis_truncates_glyphs() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+
+    let attrs = Attrs::new().family(Family::Name("Noto Sans"));
+    let text = "The quick brown fox jumps over the lazy dog";
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(20.0, 30.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_wrap(Wrap::None);
+    buffer.set_size(100.0, 100.0);
+    buffer.set_text(text, attrs, Shaping::Advanced);
+
+    // Without an ellipsis the line extends past the width
+    let clipped_glyphs = {
+        let run = buffer.layout_runs().next().unwrap();
+        assert!(run.line_w > 100.0);
+        run.glyphs.len()
+    };
+
+    buffer.set_overflow(Overflow::Ellipsis);
+    {
+        let run = buffer.layout_runs().next().unwrap();
+        assert!(run.line_w <= 100.0);
+        assert!(run.glyphs.len() < clipped_glyphs);
+        let last = run.glyphs.last().unwrap();
+        assert!(last.x + last.w <= 100.0);
+        // The ellipsis glyph does not cover any text
+        assert_eq!(last.start, last.end);
+    }
+
+    // A longer ellipsis string leaves room for no more glyphs of the text
+    let text_glyphs = buffer.layout_runs().next().unwrap().glyphs.len() - 1;
+    buffer.set_ellipsis_str("...");
+    let run = buffer.layout_runs().next().unwrap();
+    assert!(run.line_w <= 100.0);
+    let ellipsis = run.glyphs.iter().filter(|glyph| glyph.start == glyph.end);
+    assert_eq!(ellipsis.count(), 3);
+    assert!(run.glyphs.len() - 3 <= text_glyphs);
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use cosmic_text::{Align, Attrs, AttrsList, Family, FontSystem, ShapeLine, Shaping, Tag, Wrap};
+
+fn glyph_ids(font_system: &mut FontSystem, text: &str, attrs_list: &AttrsList) -> Vec<u16> {
+    let line = ShapeLine::new(font_system, text, attrs_list, Shaping::Advanced);
+    let layout = line.layout(20.0, f32::MAX, Wrap::None, Some(Align::Left));
+    layout[0]
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.glyph_id)
+        .collect()
+}
+
+#[test]
+fn font_features_builder() {
+    let attrs = Attrs::new()
+        .font_feature(*b"smcp", 1)
+        .font_feature(*b"tnum", 1)
+        .font_feature(*b"smcp", 0);
+    assert_eq!(
+        attrs.font_features.as_deref().map(Vec::as_slice),
+        Some([(Tag(*b"smcp"), 0), (Tag(*b"tnum"), 1)].as_slice())
+    );
+
+    // Spans with different features are shaped separately
+    assert!(!Attrs::new().compatible(&attrs));
+    assert!(attrs.compatible(&attrs.clone()));
+}
+
+#[test]
+fn font_features_shaping() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+
+    let attrs = Attrs::new().family(Family::Name("Noto Sans"));
+    let text = "office fluff";
+
+    // Disabling the ligature features directly matches disabling ligatures
+    let without_ligatures = glyph_ids(
+        &mut font_system,
+        text,
+        &AttrsList::new(attrs.clone().ligatures(false)),
+    );
+    let without_features = glyph_ids(
+        &mut font_system,
+        text,
+        &AttrsList::new(
+            attrs
+                .clone()
+                .font_feature(*b"liga", 0)
+                .font_feature(*b"clig", 0),
+        ),
+    );
+    assert_eq!(without_features, without_ligatures);
+
+    // Features only apply to their span
+    let mut attrs_list = AttrsList::new(attrs.clone());
+    attrs_list.add_span(7..12, attrs.clone().ligatures(false));
+    let mut features_list = AttrsList::new(attrs.clone());
+    features_list.add_span(
+        7..12,
+        attrs
+            .clone()
+            .font_feature(*b"liga", 0)
+            .font_feature(*b"clig", 0),
+    );
+    assert_eq!(
+        glyph_ids(&mut font_system, text, &features_list),
+        glyph_ids(&mut font_system, text, &attrs_list)
+    );
+}
+
+#[test]
+fn font_variations() {
+    let attrs = Attrs::new()
+        .font_variation(*b"wght", 300.0)
+        .font_variation(*b"wdth", 75.0)
+        .font_variation(*b"wght", 650.0);
+    let font_variations = attrs.font_variations.as_deref().unwrap();
+    assert_eq!(
+        font_variations.to_vec(),
+        [(Tag(*b"wght"), 650.0), (Tag(*b"wdth"), 75.0)]
+    );
+    assert_eq!(font_variations.get(Tag(*b"wdth")), Some(75.0));
+    assert_eq!(font_variations.get(Tag(*b"slnt")), None);
+    assert!(!Attrs::new().compatible(&attrs));
+    assert_ne!(attrs.clone_owned(), Attrs::new().clone_owned());
+
+    // Axes that the font does not have are ignored
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let text = "Variable";
+    let attrs = Attrs::new().family(Family::Name("Noto Sans"));
+    assert_eq!(
+        glyph_ids(
+            &mut font_system,
+            text,
+            &AttrsList::new(attrs.clone().font_variation(*b"wght", 650.0))
+        ),
+        glyph_ids(&mut font_system, text, &AttrsList::new(attrs))
+    );
+}
+
+#[test]
+fn font_variations_cache_key() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let attrs = Attrs::new().family(Family::Name("Noto Sans"));
+    let text = "ab";
+
+    // The axes are passed on to the glyphs, so they are rasterized with the same values
+    let attrs_list = AttrsList::new(attrs.clone().font_variation(*b"wght", 650.0));
+    let line = ShapeLine::new(&mut font_system, text, &attrs_list, Shaping::Advanced);
+    let layout = line.layout(20.0, f32::MAX, Wrap::None, Some(Align::Left));
+    let glyph = &layout[0].glyphs[0];
+    let cache_key = glyph.physical((0.0, 0.0), 1.0).cache_key;
+    assert_eq!(
+        cache_key.font_variations,
+        attrs_list.defaults().font_variations
+    );
+
+    // Glyphs with different axes do not share a cache key
+    let line = ShapeLine::new(
+        &mut font_system,
+        text,
+        &AttrsList::new(attrs),
+        Shaping::Advanced,
+    );
+    let layout = line.layout(20.0, f32::MAX, Wrap::None, Some(Align::Left));
+    let plain_key = layout[0].glyphs[0].physical((0.0, 0.0), 1.0).cache_key;
+    assert_eq!(plain_key.font_variations, None);
+    assert_eq!(plain_key.glyph_id, cache_key.glyph_id);
+    assert_ne!(plain_key, cache_key);
+}
@@ -0,0 +1,93 @@
+use cosmic_text::{fontdb, Attrs, Buffer, Family, FontSystem, Metrics, Shaping, Weight};
+
+#[test]
+fn remove_font() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let sans = font_system.load_font_static(include_bytes!("../fonts/NotoSans-Regular.ttf"))[0];
+    let mono = font_system.load_font_static(include_bytes!("../fonts/FiraMono-Medium.ttf"))[0];
+
+    let font = font_system.get_font(sans).unwrap();
+    let matches = font_system.get_font_matches(Attrs::new().family(Family::Name("Noto Sans")));
+    assert!(matches.contains(&sans));
+
+    font_system.remove_font(sans);
+    assert!(font_system.get_font(sans).is_none());
+    assert_eq!(font_system.list_font_ids(), [mono]);
+    let matches = font_system.get_font_matches(Attrs::new().family(Family::Name("Noto Sans")));
+    assert!(!matches.contains(&sans));
+
+    // Fonts that were already loaded stay valid
+    assert!(font.glyph_id_for_char('a').is_some());
+}
+
+#[test]
+fn list_fonts() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let sans = font_system.load_font_static(include_bytes!("../fonts/NotoSans-Regular.ttf"))[0];
+    let mono = font_system.load_font_static(include_bytes!("../fonts/FiraMono-Medium.ttf"))[0];
+    font_system.load_font_static(include_bytes!("../fonts/FiraMono-Medium.ttf"));
+
+    let fonts = font_system.list_fonts();
+    assert_eq!(fonts.len(), 3);
+    assert_eq!(fonts[0].id, sans);
+    assert_eq!(fonts[0].family, "Noto Sans");
+    assert_eq!(fonts[0].style, "Normal");
+    assert_eq!(fonts[0].weight, Weight::NORMAL);
+    assert!(!fonts[0].is_monospace);
+    assert_eq!(fonts[1].id, mono);
+    assert_eq!(fonts[1].family, "Fira Mono");
+    assert_eq!(fonts[1].weight, Weight::MEDIUM);
+    assert!(fonts[1].is_monospace);
+
+    assert_eq!(font_system.list_families(), ["Fira Mono", "Noto Sans"]);
+}
+
+#[test]
+fn query_coverage() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let sans = font_system.load_font_static(include_bytes!("../fonts/NotoSans-Regular.ttf"))[0];
+    let hebrew = font_system.load_font_static(include_bytes!("../fonts/NotoSansHebrew.ttf"))[0];
+
+    let font = font_system.get_font(hebrew).unwrap();
+    assert!(font.has_glyph('א'));
+    assert!(!font.has_glyph('ж'));
+
+    assert_eq!(font_system.query_coverage('ж'), [sans]);
+    assert_eq!(font_system.query_coverage('א'), [hebrew]);
+    assert!(font_system.query_coverage('\u{10FFFF}').is_empty());
+}
+
+#[test]
+fn font_for_glyph() {
+    let mut font_system =
+        FontSystem::new_with_locale_and_db("en-US".into(), fontdb::Database::new());
+    let sans = font_system.load_font_static(include_bytes!("../fonts/NotoSans-Regular.ttf"))[0];
+    let hebrew = font_system.load_font_static(include_bytes!("../fonts/NotoSansHebrew.ttf"))[0];
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 1000.0, 1000.0);
+    buffer.set_text(&mut font_system, "a א\nb", Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(&mut font_system);
+
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    let font_ids: Vec<_> = runs[0]
+        .glyphs
+        .iter()
+        .map(|glyph| {
+            buffer
+                .font_for_glyph(&mut font_system, &runs[0], glyph)
+                .unwrap()
+                .id()
+        })
+        .collect();
+    assert!(font_ids.contains(&sans));
+    assert!(font_ids.contains(&hebrew));
+
+    // A glyph of another run is rejected
+    assert!(buffer
+        .font_for_glyph(&mut font_system, &runs[0], &runs[1].glyphs[0])
+        .is_none());
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use cosmic_text::Attrs;
+
+#[test]
+fn max_lines() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = common::buffer_with_text(
+        &mut font_system,
+        "one\ntwo\nthree\nfour",
+        Attrs::new(),
+        60.0,
+    );
+    assert_eq!(buffer.max_lines(), None);
+    assert!(!buffer.exceeds_max_lines());
+    assert_eq!(buffer.layout_runs().count(), 4);
+
+    buffer.set_max_lines(Some(2));
+    assert_eq!(buffer.max_lines(), Some(2));
+    assert!(buffer.exceeds_max_lines());
+    let lines: Vec<usize> = buffer.layout_runs().map(|run| run.line_i).collect();
+    assert_eq!(lines, [0, 1]);
+
+    buffer.set_max_lines(Some(4));
+    assert!(!buffer.exceeds_max_lines());
+    assert_eq!(buffer.layout_runs().count(), 4);
+
+    buffer.set_max_lines(None);
+    assert!(!buffer.exceeds_max_lines());
+}
+
+#[test]
+fn max_lines_wrapped() {
+    // Wrapped layout lines count towards the limit
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = common::buffer_with_text(
+        &mut font_system,
+        "one two three four five six",
+        Attrs::new(),
+        60.0,
+    );
+    let layout_lines = buffer.layout_runs().count();
+    assert!(layout_lines > 2);
+    assert_eq!(buffer.lines.len(), 1);
+
+    buffer.set_max_lines(Some(2));
+    assert!(buffer.exceeds_max_lines());
+    assert_eq!(buffer.layout_runs().count(), 2);
+
+    buffer.set_max_lines(Some(layout_lines));
+    assert!(!buffer.exceeds_max_lines());
+    assert_eq!(buffer.layout_runs().count(), layout_lines);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use cosmic_text::{Action, Attrs, Buffer, Cursor, Edit, Metrics, ParagraphStyle, Shaping};
+
+#[test]
+fn scroll_with_spacing() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_text(
+        &mut font_system,
+        "0\n1\n2\n3\n4\n5\n6\n7\n8\n9",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    for line in buffer.lines.iter_mut() {
+        line.set_paragraph_style(ParagraphStyle {
+            spacing_after: 10.0,
+            ..Default::default()
+        });
+    }
+    // Every line takes 20 * 1.5 + 10 pixels, so only two lines fit in 100 pixels
+    buffer.set_line_spacing(1.5);
+    buffer.set_size(&mut font_system, 500.0, 100.0);
+    buffer.shape_until_scroll(&mut font_system);
+    assert_eq!(buffer.visible_lines(), 2);
+    assert_eq!(buffer.layout_runs().count(), 2);
+
+    // Following the cursor scrolls until its line is the last visible one
+    buffer.shape_until_cursor(&mut font_system, Cursor::new(5, 0));
+    assert_eq!(buffer.scroll(), 4);
+    let lines: Vec<usize> = buffer.layout_runs().map(|run| run.line_i).collect();
+    assert_eq!(lines, [4, 5]);
+
+    // The last line is visible at the largest scroll
+    assert_eq!(buffer.max_scroll(), 8);
+    buffer.set_scroll_fraction(1.0);
+    let lines: Vec<usize> = buffer.layout_runs().map(|run| run.line_i).collect();
+    assert_eq!(lines, [8, 9]);
+
+    // Scrolling past the end is clamped
+    buffer.set_scroll(20);
+    buffer.shape_until_scroll(&mut font_system);
+    assert_eq!(buffer.scroll(), 8);
+}
+
+#[test]
+fn scroll_pixels_with_line_spacing() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut editor = common::editor_with_text(&mut font_system, "0\n1\n2\n3\n4\n5\n6\n7\n8\n9");
+    editor.buffer_mut().set_line_spacing(2.0);
+    editor.buffer_mut().set_size(&mut font_system, 500.0, 100.0);
+
+    // Every layout line is 40 pixels tall, the remainder is carried over
+    editor.action(&mut font_system, Action::ScrollPixels { pixels: 60.0 });
+    assert_eq!(editor.buffer().scroll(), 1);
+    editor.action(&mut font_system, Action::ScrollPixels { pixels: 20.0 });
+    assert_eq!(editor.buffer().scroll(), 2);
+}
+
+#[test]
+fn estimated_line_height_from_font() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 40.0));
+    buffer.set_text(&mut font_system, "0\n1\n2", Attrs::new(), Shaping::Advanced);
+    buffer.set_size(&mut font_system, 500.0, 100.0);
+    buffer.shape_until_scroll(&mut font_system);
+
+    let font_id = font_system.get_font_matches(Attrs::new())[0];
+    let font = font_system.get_font(font_id).unwrap();
+    let font_line_height = font.line_height_px(14.0).unwrap();
+    assert_ne!(font_line_height, 40.0);
+    assert_eq!(buffer.estimated_line_height(), font_line_height);
+
+    buffer.set_line_spacing(2.0);
+    assert_eq!(buffer.estimated_line_height(), font_line_height * 2.0);
+
+    buffer.set_estimated_line_height(Some(30.0));
+    assert_eq!(buffer.estimated_line_height(), 30.0);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use cosmic_text::{Attrs, Cursor};
+
+fn range(line: usize, start: usize, end: usize) -> (Cursor, Cursor) {
+    (Cursor::new(line, start), Cursor::new(line, end))
+}
+
+#[test]
+fn search_normalized() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    // Decomposed and precomposed forms of "café"
+    let buffer = common::buffer_with_text(
+        &mut font_system,
+        "cafe\u{301} café\nCAFÉ",
+        Attrs::new(),
+        1000.0,
+    );
+
+    assert_eq!(
+        buffer.search("café", true),
+        [range(0, 0, 6), range(0, 7, 12)]
+    );
+    assert_eq!(
+        buffer.search("cafe\u{301}", true),
+        [range(0, 0, 6), range(0, 7, 12)]
+    );
+    assert_eq!(
+        buffer.search("café", false),
+        [range(0, 0, 6), range(0, 7, 12), range(1, 0, 5)]
+    );
+
+    // Matches never split a base character from its combining marks
+    assert!(buffer.search("e", false).is_empty());
+    assert!(buffer.search("\u{301}", true).is_empty());
+
+    assert_eq!(
+        buffer.search_next("café", Cursor::new(0, 1), true),
+        Some(range(0, 7, 12))
+    );
+    assert_eq!(
+        buffer.search_next("café", Cursor::new(0, 8), false),
+        Some(range(1, 0, 5))
+    );
+}
+
+#[test]
+fn search_multi_line_query() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let buffer = common::buffer_with_text(&mut font_system, "one\ntwo", Attrs::new(), 1000.0);
+
+    // Lines are searched on their own, so queries with newlines never match
+    assert!(buffer.search("one\ntwo", true).is_empty());
+    assert_eq!(buffer.search_next("e\nt", Cursor::new(0, 0), true), None);
+    assert!(buffer.search("", true).is_empty());
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use cosmic_text::{fontdb, Attrs, Buffer, Family, Metrics, Shaping};
+
+fn font_ids(buffer: &Buffer, line_i: usize) -> Vec<fontdb::ID> {
+    buffer.lines[line_i].shape_opt().unwrap().spans[0].words[0]
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.font_id)
+        .collect()
+}
+
+#[test]
+fn shape_cache() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf", "FiraMono-Medium.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 500.0, 500.0);
+    buffer.enable_shape_cache(16);
+
+    let sans = Attrs::new().family(Family::Name("Noto Sans"));
+    let mono = Attrs::new().family(Family::Name("Fira Mono"));
+    buffer.set_rich_text(
+        &mut font_system,
+        [("abc\n", sans.clone()), ("abc\n", mono), ("abc", sans)],
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system);
+
+    // Lines with the same text and attributes share a shape, others are shaped on their own
+    assert_eq!(font_ids(&buffer, 0), font_ids(&buffer, 2));
+    assert_ne!(font_ids(&buffer, 0), font_ids(&buffer, 1));
+
+    // Removing a font drops the cached shapes, so the font is no longer used
+    let sans_id = font_ids(&buffer, 0)[0];
+    font_system.remove_font(sans_id);
+    for line in buffer.lines.iter_mut() {
+        line.reset();
+    }
+    buffer.shape_until_scroll(&mut font_system);
+    assert!(!font_ids(&buffer, 0).contains(&sans_id));
+    assert!(!font_ids(&buffer, 2).contains(&sans_id));
+}
+
+#[test]
+fn shape_cache_lru() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 500.0, 500.0);
+    buffer.enable_shape_cache(2);
+    buffer.set_text(
+        &mut font_system,
+        "a\nb\na\nc\na\nb",
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system);
+
+    let shared = |i: usize, j: usize| {
+        std::ptr::eq(
+            buffer.lines[i].shape_opt().unwrap(),
+            buffer.lines[j].shape_opt().unwrap(),
+        )
+    };
+    // Cached shapes are shared, not copied
+    assert!(shared(0, 2));
+    // Using "a" again made "b" the least recently used shape, so "c" evicted it
+    assert!(shared(0, 4));
+    assert!(!shared(1, 5));
+}
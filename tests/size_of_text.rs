@@ -0,0 +1,26 @@
+mod common;
+
+use cosmic_text::{Attrs, AttrsList, Buffer, Family, Metrics, Shaping, Wrap};
+
+#[test]
+fn size_of_text_matches_unwrapped_buffer() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+
+    let attrs = Attrs::new().family(Family::Name("Noto Sans"));
+    let text = "Short\nA much longer second line of text\nThird";
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(20.0, 30.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(50.0, 500.0);
+    buffer.set_text("Unchanged", attrs.clone(), Shaping::Advanced);
+
+    let size = buffer.size_of_text(text, AttrsList::new(attrs.clone()));
+    assert_eq!(size.1, 90.0);
+
+    // The buffer keeps its own text and layout
+    assert_eq!(buffer.lines.len(), 1);
+    assert_eq!(buffer.lines[0].text(), "Unchanged");
+
+    buffer.set_wrap(Wrap::None);
+    buffer.set_text(text, attrs, Shaping::Advanced);
+    assert_eq!(buffer.content_size_unclamped(), size);
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use cosmic_text::{Align, Attrs, AttrsList, Family, ShapeLine, Shaping, Wrap};
+
+#[test]
+fn soft_hyphen_at_line_break() {
+    let mut font_system = common::font_system(&["NotoSans-Regular.ttf"]);
+
+    let attrs = AttrsList::new(Attrs::new().family(Family::Name("Noto Sans")));
+    let text = "Donaudampf\u{AD}schifffahrt";
+    let soft_hyphen = text.find('\u{AD}').unwrap();
+    let line = ShapeLine::new(&mut font_system, text, &attrs, Shaping::Advanced);
+
+    // The soft hyphen is hidden when the word fits on one line
+    let layout = line.layout(20.0, f32::MAX, Wrap::Word, Some(Align::Left));
+    assert_eq!(layout.len(), 1);
+    for glyph in layout[0].glyphs.iter() {
+        if glyph.start == soft_hyphen {
+            assert_eq!(glyph.w, 0.0);
+        }
+    }
+
+    // A hyphen is shown at the end of the first line when breaking at the soft hyphen
+    let width = layout[0].w * 0.75;
+    let layout = line.layout(20.0, width, Wrap::Word, Some(Align::Left));
+    assert_eq!(layout.len(), 2);
+    let hyphen = layout[0].glyphs.last().unwrap();
+    assert_eq!(hyphen.start, soft_hyphen);
+    assert!(hyphen.w > 0.0);
+    assert!(layout[0].w <= width);
+    assert!((layout[0].w - (hyphen.x + hyphen.w)).abs() < 0.01);
+    assert!(layout[1]
+        .glyphs
+        .iter()
+        .all(|glyph| glyph.start > soft_hyphen));
+}
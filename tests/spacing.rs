@@ -0,0 +1,90 @@
+mod common;
+
+use cosmic_text::{Attrs, AttrsOwned, Buffer, Family};
+
+const FONTS: &[&str] = &["FiraMono-Medium.ttf"];
+
+/// Get the x and width of each glyph in the first layout line
+fn glyphs(buffer: &Buffer) -> Vec<(f32, f32)> {
+    let run = buffer.layout_runs().next().unwrap();
+    run.glyphs.iter().map(|glyph| (glyph.x, glyph.w)).collect()
+}
+
+fn hit_index(buffer: &Buffer, x: f32) -> usize {
+    let cursor = buffer.hit(x, 10.0).unwrap();
+    assert_eq!(cursor.line, 0);
+    cursor.index
+}
+
+#[test]
+fn letter_spacing() {
+    let mut font_system = common::font_system(FONTS);
+    let mono = Attrs::new().family(Family::Name("Fira Mono"));
+    let plain = glyphs(&common::buffer_with_text(
+        &mut font_system,
+        "abc",
+        mono.clone(),
+        500.0,
+    ));
+    let buffer =
+        common::buffer_with_text(&mut font_system, "abc", mono.letter_spacing(20.0), 500.0);
+    let spaced = glyphs(&buffer);
+
+    // Each glyph advances by its own width plus the spacing
+    for (i, (spaced, plain)) in spaced.iter().zip(&plain).enumerate() {
+        assert!((spaced.1 - (plain.1 + 20.0)).abs() < 0.01, "{spaced:?}");
+        assert!(
+            (spaced.0 - (plain.0 + 20.0 * i as f32)).abs() < 0.01,
+            "{spaced:?}"
+        );
+    }
+
+    // The spacing is part of the glyph for hit testing
+    let (x, w) = spaced[1];
+    assert_eq!(hit_index(&buffer, x + w * 0.25), 1);
+    assert_eq!(hit_index(&buffer, x + w * 0.75), 2);
+}
+
+#[test]
+fn word_spacing() {
+    let mut font_system = common::font_system(FONTS);
+    let mono = Attrs::new().family(Family::Name("Fira Mono"));
+    let plain = glyphs(&common::buffer_with_text(
+        &mut font_system,
+        "a b",
+        mono.clone(),
+        500.0,
+    ));
+    let buffer = common::buffer_with_text(&mut font_system, "a b", mono.word_spacing(30.0), 500.0);
+    let spaced = glyphs(&buffer);
+
+    // Only whitespace glyphs get the extra advance
+    assert!((spaced[0].1 - plain[0].1).abs() < 0.01, "{spaced:?}");
+    assert!(
+        (spaced[1].1 - (plain[1].1 + 30.0)).abs() < 0.01,
+        "{spaced:?}"
+    );
+    assert!(
+        (spaced[2].0 - (plain[2].0 + 30.0)).abs() < 0.01,
+        "{spaced:?}"
+    );
+
+    let (x, w) = spaced[1];
+    assert_eq!(hit_index(&buffer, x + w * 0.25), 1);
+    assert_eq!(hit_index(&buffer, x + w * 0.75), 2);
+    let (x, w) = spaced[2];
+    assert_eq!(hit_index(&buffer, x + w * 0.25), 2);
+    assert_eq!(hit_index(&buffer, x + w * 0.75), 3);
+}
+
+#[test]
+fn spacing_getters() {
+    let attrs = Attrs::new().letter_spacing(-1.5).word_spacing(4.0);
+    assert_eq!(attrs.get_letter_spacing(), -1.5);
+    assert_eq!(attrs.get_word_spacing(), 4.0);
+
+    let owned = AttrsOwned::new(attrs);
+    assert_eq!(owned.get_letter_spacing(), -1.5);
+    assert_eq!(owned.get_word_spacing(), 4.0);
+    assert_eq!(Attrs::new().get_letter_spacing(), 0.0);
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use cosmic_text::{Align, Attrs, AttrsList, Buffer, BufferLine, Family, Metrics, Shaping, Wrap};
+
+fn glyph_positions(buffer: &Buffer) -> Vec<(f32, f32)> {
+    let run = buffer.layout_runs().next().unwrap();
+    run.glyphs.iter().map(|glyph| (glyph.x, glyph.w)).collect()
+}
+
+fn assert_near(a: f32, b: f32) {
+    assert!((a - b).abs() < 0.01, "{a} != {b}");
+}
+
+#[test]
+fn tab_size() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+    let attrs = Attrs::new().family(Family::Name("Fira Mono"));
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+    assert_eq!(buffer.tab_size(), 4);
+
+    buffer.set_text(" ", attrs.clone(), Shaping::Advanced);
+    let space = glyph_positions(&buffer)[0].1;
+    assert!(space > 0.0);
+
+    // Tabs advance to the next multiple of the tab size
+    buffer.set_text("a\tb", attrs.clone(), Shaping::Advanced);
+    let glyphs = glyph_positions(&buffer);
+    assert_near(glyphs[1].1, 3.0 * space);
+    assert_near(glyphs[2].0, 4.0 * space);
+
+    buffer.set_text("\tb", attrs.clone(), Shaping::Advanced);
+    assert_near(glyph_positions(&buffer)[1].0, 4.0 * space);
+
+    buffer.set_text("abcd\tb", attrs.clone(), Shaping::Advanced);
+    assert_near(glyph_positions(&buffer)[5].0, 8.0 * space);
+
+    buffer.set_tab_size(2);
+    buffer.set_text("a\tb", attrs, Shaping::Advanced);
+    assert_near(glyph_positions(&buffer)[2].0, 2.0 * space);
+}
+
+#[test]
+fn tab_size_rtl() {
+    let mut font_system = common::font_system(&["NotoSansHebrew.ttf"]);
+    let attrs = Attrs::new().family(Family::Name("Noto Sans Hebrew"));
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+
+    buffer.set_text(" ", attrs.clone(), Shaping::Advanced);
+    let space = glyph_positions(&buffer)[0].1;
+
+    // Tabs are measured from the right edge of right-to-left lines
+    buffer.set_text("א\tב", attrs, Shaping::Advanced);
+    let run = buffer.layout_runs().next().unwrap();
+    let end = run
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.x + glyph.w)
+        .fold(0.0, f32::max);
+    let tab = run
+        .glyphs
+        .iter()
+        .find(|glyph| &run.text[glyph.start..glyph.end] == "\t")
+        .unwrap();
+    assert_near(end - tab.x, 4.0 * space);
+}
+
+#[test]
+fn tab_stops() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+    let attrs = Attrs::new().family(Family::Name("Fira Mono"));
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_text(" ", attrs.clone(), Shaping::Advanced);
+    let space = glyph_positions(&buffer)[0].1;
+
+    // Tabs advance to the next tab stop, then to multiples of the tab size
+    buffer.set_tab_stops(vec![3.5 * space]);
+    buffer.set_text("a\tb", attrs.clone(), Shaping::Advanced);
+    assert_near(glyph_positions(&buffer)[2].0, 3.5 * space);
+    buffer.set_text("abcd\tb", attrs, Shaping::Advanced);
+    assert_near(glyph_positions(&buffer)[5].0, 8.0 * space);
+}
+
+#[test]
+fn tab_stops_rtl() {
+    let mut font_system = common::font_system(&["NotoSansHebrew.ttf"]);
+    let attrs = Attrs::new().family(Family::Name("Noto Sans Hebrew"));
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+
+    // Tab stops are measured from the right edge of right-to-left lines
+    buffer.set_tab_stops(vec![50.0]);
+    buffer.set_text("א\tב", attrs, Shaping::Advanced);
+    let run = buffer.layout_runs().next().unwrap();
+    let end = run
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.x + glyph.w)
+        .fold(0.0, f32::max);
+    let tab = run
+        .glyphs
+        .iter()
+        .find(|glyph| &run.text[glyph.start..glyph.end] == "\t")
+        .unwrap();
+    assert_near(end - tab.x, 50.0);
+}
+
+#[test]
+fn tabs_wrap_and_align() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+    let attrs = Attrs::new().family(Family::Name("Fira Mono"));
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    let mut buffer = buffer.borrow_with(&mut font_system);
+    buffer.set_size(1000.0, 100.0);
+    buffer.set_text(" ", attrs.clone(), Shaping::Advanced);
+    let space = glyph_positions(&buffer)[0].1;
+
+    // The tabs are widened while wrapping, so the text after them wraps within the width
+    let width = 10.5 * space;
+    buffer.set_size(width, 100.0);
+    buffer.set_text("a\tb\tc d", attrs.clone(), Shaping::Advanced);
+    let runs: Vec<_> = buffer.layout_runs().collect();
+    assert_eq!(runs.len(), 2);
+    for run in runs.iter() {
+        for glyph in run.glyphs.iter() {
+            if !run.text[glyph.start..glyph.end].trim().is_empty() {
+                assert!(glyph.x + glyph.w <= width + 0.01);
+            }
+        }
+    }
+    assert_eq!(&runs[1].text[runs[1].glyphs[0].start..], "d");
+
+    // Aligned lines include the tab advances
+    buffer.set_size(20.0 * space, 100.0);
+    buffer.set_align(Some(Align::Right));
+    buffer.set_text("a\tb", attrs, Shaping::Advanced);
+    let glyphs = glyph_positions(&buffer);
+    assert_near(glyphs[0].0, 15.0 * space);
+    assert_near(glyphs[2].0 + glyphs[2].1, 20.0 * space);
+}
+
+#[test]
+fn buffer_line_tabs() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+    let attrs = Attrs::new().family(Family::Name("Fira Mono"));
+
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 1000.0, 100.0);
+    buffer.set_text(&mut font_system, "a\tb", attrs.clone(), Shaping::Advanced);
+    let expected = glyph_positions(&buffer);
+
+    // Lines laid out directly use the same default tab size as a buffer
+    let mut line = BufferLine::new("a\tb", AttrsList::new(attrs), Shaping::Advanced);
+    let layout = line.layout(&mut font_system, 14.0, 1000.0, Wrap::Word);
+    let glyphs: Vec<(f32, f32)> = layout[0]
+        .glyphs
+        .iter()
+        .map(|glyph| (glyph.x, glyph.w))
+        .collect();
+    assert_eq!(glyphs.len(), expected.len());
+    for (glyph, expected) in glyphs.iter().zip(expected.iter()) {
+        assert_near(glyph.0, expected.0);
+        assert_near(glyph.1, expected.1);
+    }
+}
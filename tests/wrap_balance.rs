@@ -0,0 +1,55 @@
+mod common;
+
+use cosmic_text::{Attrs, Buffer, Family, Metrics, Shaping};
+
+fn line_widths(buffer: &Buffer) -> Vec<f32> {
+    buffer.layout_runs().map(|run| run.line_w).collect()
+}
+
+#[test]
+fn wrap_balance() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 200.0, 1000.0);
+    buffer.set_text(
+        &mut font_system,
+        "aaaa aaaa aaaa aaaa aaaa",
+        Attrs::new().family(Family::Name("Fira Mono")),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system);
+    let unbalanced = line_widths(&buffer);
+    assert_eq!(unbalanced.len(), 2);
+
+    // Balancing keeps the number of lines, making them more equal
+    buffer.set_wrap_balance(&mut font_system, true);
+    assert!(buffer.wrap_balance());
+    let balanced = line_widths(&buffer);
+    assert_eq!(balanced.len(), 2);
+    assert!(balanced[0] < unbalanced[0]);
+    assert!(balanced[1] > unbalanced[1]);
+    assert!(balanced[0] - balanced[1] < unbalanced[0] - unbalanced[1]);
+
+    buffer.set_wrap_balance(&mut font_system, false);
+    assert_eq!(line_widths(&buffer), unbalanced);
+}
+
+#[test]
+fn wrap_balance_single_line() {
+    let mut font_system = common::font_system(&["FiraMono-Medium.ttf"]);
+    let mut buffer = Buffer::new(&mut font_system, Metrics::new(14.0, 20.0));
+    buffer.set_size(&mut font_system, 200.0, 1000.0);
+    buffer.set_text(
+        &mut font_system,
+        "aaaa aaaa",
+        Attrs::new().family(Family::Name("Fira Mono")),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(&mut font_system);
+    let unbalanced = line_widths(&buffer);
+
+    // Lines that fit are not wrapped
+    buffer.set_wrap_balance(&mut font_system, true);
+    assert_eq!(line_widths(&buffer), unbalanced);
+    assert_eq!(unbalanced.len(), 1);
+}
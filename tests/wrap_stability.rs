@@ -59,7 +59,7 @@ fn stable_wrap() {
     .chain(BidiParagraphs::new(&hello_sample));
 
     for text in cases {
-        for wrap in [Wrap::Word, Wrap::Glyph] {
+        for wrap in [Wrap::Word, Wrap::Glyph, Wrap::WordOrGlyph] {
             for start_width in [f32::MAX, 80.0, 198.2132, 20.0, 4.0, 300.0] {
                 check_wrap(text, wrap, start_width);
                 let with_spaces = format!("{text}            ");